@@ -0,0 +1,28 @@
+use aoc2020::day1::{k_sum, k_sum_permutations};
+use aoc2020::input::Inputs;
+use aoc2020::parse::numbers;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+//The sorted k-sum against the original search through every permutation
+fn k_sum_benchmark(c: &mut Criterion) {
+    let input = Inputs::from_env().day(1).unwrap();
+    let expenses: Vec<i32> = numbers(&input).unwrap();
+    let mut group = c.benchmark_group("day1 k-sum");
+    group.sample_size(10);
+    for k in 2..=3 {
+        group.bench_with_input(BenchmarkId::new("sorted", k), &k, |b, &k| {
+            b.iter(|| k_sum(&expenses, k, 2020))
+        });
+        group.bench_with_input(BenchmarkId::new("permutations", k), &k, |b, &k| {
+            b.iter(|| k_sum_permutations(&expenses, k, 2020))
+        });
+    }
+    //Too slow to try every permutation of four
+    group.bench_with_input(BenchmarkId::new("sorted", 4), &4, |b, &k| {
+        b.iter(|| k_sum(&expenses, k, 2020))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, k_sum_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,25 @@
+use aoc2020::day2::Day2;
+use aoc2020::input::Inputs;
+use aoc2020::Solution;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+//Both parts on the real input, all ASCII, and on the same input with a
+//non-ASCII password in every line, which always takes the Unicode path
+fn policies_benchmark(c: &mut Criterion) {
+    let ascii = Inputs::from_env().day(2).unwrap();
+    let unicode: String = ascii.lines().map(|line| format!("{}é\n", line)).collect();
+    let mut group = c.benchmark_group("day2 policies");
+    for (name, input) in [("ascii", &ascii), ("unicode", &unicode)] {
+        let databases = Day2::parse(input).unwrap();
+        group.bench_function(format!("part1/{}", name), |b| {
+            b.iter(|| Day2::part1(&databases))
+        });
+        group.bench_function(format!("part2/{}", name), |b| {
+            b.iter(|| Day2::part2(&databases))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, policies_benchmark);
+criterion_main!(benches);
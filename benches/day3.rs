@@ -0,0 +1,54 @@
+use aoc2020::day3::{Day3, Slope};
+use aoc2020::inputgen;
+use aoc2020::Solution;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+fn wide_forest(width: usize, height: usize) -> String {
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    let mut forest = String::with_capacity((width + 1) * height);
+    for _ in 0..height {
+        forest.extend((0..width).map(|_| if rng.gen_bool(0.25) { '#' } else { '.' }));
+        forest.push('\n');
+    }
+    forest
+}
+
+//Each slope on its own on the grid of cells, against all of them in one go
+//over the packed rows
+fn packed_benchmark(c: &mut Criterion) {
+    let tall = inputgen::generate(3, 200_000, 3).unwrap().input;
+    let wide = wide_forest(2000, 5000);
+    let puzzle: Vec<Slope> = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]
+        .iter()
+        .map(|&(right, down)| Slope { right, down })
+        .collect();
+    let many: Vec<Slope> = (1..=32)
+        .flat_map(|right| (1..=4).map(move |down| Slope { right, down }))
+        .collect();
+    let mut group = c.benchmark_group("day3 packed");
+    group.sample_size(10);
+    for (name, input) in [("tall", &tall), ("wide", &wide)] {
+        let map = Day3::parse(input).unwrap();
+        let packed = map.packed();
+        for (count, slopes) in [("5 slopes", &puzzle), ("128 slopes", &many)] {
+            let id = format!("{}/{}", name, count);
+            group.bench_function(BenchmarkId::new("grid", &id), |b| {
+                b.iter(|| -> Vec<usize> {
+                    slopes
+                        .iter()
+                        .map(|&slope| map.count_trees_traversed(slope))
+                        .collect()
+                })
+            });
+            group.bench_function(BenchmarkId::new("packed", &id), |b| {
+                b.iter(|| packed.count_trees_batch(slopes))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, packed_benchmark);
+criterion_main!(benches);
@@ -1,22 +1,82 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use aoc2020::input::Inputs;
+use aoc2020::{solutions, Driver, Part, Result, Runner};
+use criterion::measurement::WallTime;
+use criterion::{criterion_group, criterion_main, BenchmarkGroup, Criterion};
+use std::time::{Duration, Instant};
 
-use aoc2020::{day1, day10, day11, day12, day14, day2, day3, day6, day7, day8, day9};
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    Parse,
+    Part(Part),
+}
+
+//Benchmarks a single stage of a day, the others just run as needed
+struct Bench<'a, 'c> {
+    group: &'a mut BenchmarkGroup<'c, WallTime>,
+    stage: Stage,
+    day: u8,
+}
+
+impl Bench<'_, '_> {
+    fn bench<T>(&mut self, stage: &mut dyn FnMut() -> Result<T>) -> Result<()> {
+        //Fails before benchmarking something that doesn't work, and keeps
+        //the slow days (15, 17) from taking minutes
+        let start = Instant::now();
+        stage()?;
+        let sample_size = match start.elapsed() {
+            elapsed if elapsed > Duration::from_millis(100) => 10,
+            _ => 100,
+        };
+        self.group.sample_size(sample_size);
+        let id = format!("day{}", self.day);
+        self.group.bench_function(id, |b| b.iter(&mut *stage));
+        Ok(())
+    }
+}
+
+impl Driver for Bench<'_, '_> {
+    fn parse(&mut self, parse: &mut dyn FnMut() -> Result<()>) -> Result<()> {
+        match self.stage {
+            Stage::Parse => self.bench(parse),
+            _ => Ok(()),
+        }
+    }
+
+    fn part(&mut self, part: Part, solve: &mut dyn FnMut() -> Result<String>) -> Result<()> {
+        match self.stage {
+            Stage::Part(stage) if stage == part => self.bench(solve),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn bench_stage(c: &mut Criterion, stage: Stage, days: &[&dyn Runner]) {
+    let inputs = Inputs::from_env();
+    let mut group = c.benchmark_group(match stage {
+        Stage::Parse => "parse".to_string(),
+        Stage::Part(part) => format!("part{}", part),
+    });
+    for runner in days {
+        let day = runner.day();
+        let input = inputs.day(day).unwrap();
+        let mut bench = Bench {
+            group: &mut group,
+            stage,
+            day,
+        };
+        if let Err(error) = runner.drive(&input, &mut bench) {
+            panic!("day {}: {}", day, error);
+        }
+    }
+    group.finish();
+}
 
 fn criterion_benchmark(c: &mut Criterion) {
-    //c.bench_function("day1part1", |b| b.iter(|| day1::tests::part1()));
-    //c.bench_function("day1part2", |b| b.iter(|| day1::tests::part2()));
-    //c.bench_function("day2part1", |b| b.iter(|| day2::tests::part1()));
-    //c.bench_function("day2part2", |b| b.iter(|| day2::tests::part2()));
-    //c.bench_function("day3part1", |b| b.iter(|| day3::tests::part1()));
-    //c.bench_function("main", |b| b.iter(|| main::main()));
-    //c.bench_function("main", |b| b.iter(|| day6::tests::part2()));
-    //c.bench_function("main", |b| b.iter(|| day7::tests::part1()));
-    //c.bench_function("main", |b| b.iter(|| day8::tests::part1()));
-    //c.bench_function("main", |b| b.iter(|| day9::tests::part2()));
-    //c.bench_function("main", |b| b.iter(|| day10::tests::part2()));
-    //c.bench_function("main", |b| b.iter(|| day11::tests::part2()));
-    //c.bench_function("main", |b| b.iter(|| day12::tests::part2()));
-    //c.bench_function("main", |b| b.iter(|| day14::tests::part2()));
+    let days = solutions();
+    bench_stage(c, Stage::Parse, days);
+    for &part in &Part::ALL {
+        bench_stage(c, Stage::Part(part), days);
+    }
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -0,0 +1,10 @@
+#!/bin/sh
+# Seeds the corpus of each target with its day's puzzle input and examples
+cd "$(dirname "$0")" || exit 1
+for target in fuzz_targets/*.rs; do
+    day=$(basename "$target" .rs)
+    mkdir -p "corpus/$day"
+    for input in ../inputs/"$day".txt ../inputs/"$day"_*.txt; do
+        [ -f "$input" ] && cp "$input" "corpus/$day/"
+    done
+done
@@ -1,42 +1,375 @@
+use crate::error::{AocError, Result};
+use crate::parse::numbers;
+use crate::Solution;
 use itertools::Itertools;
 
-fn get_input() -> Vec<Result<i32, std::num::ParseIntError>> {
-    let raw = include_str!("../inputs/day1_p1.txt");
-    let expenses = raw.lines().map(|ex| ex.parse::<i32>()).collect();
-    expenses
+fn get_input(raw: &str) -> Result<Vec<i32>> {
+    numbers(raw)
 }
 
-fn combinations(number_permutations: i32) -> Vec<Vec<i32>> {
-    let permutations = get_input()
-        .iter()
-        .map(|ex| ex.clone().unwrap())
-        .permutations(number_permutations as usize)
+/// Entries adding up to a target: their positions in the input and their values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entries {
+    pub indices: Vec<usize>,
+    pub values: Vec<i32>,
+}
+
+impl Entries {
+    fn new(expenses: &[i32], indices: Vec<usize>) -> Self {
+        let values = indices.iter().map(|&i| expenses[i]).collect();
+        Entries { indices, values }
+    }
+
+    /// The product of the values, or `None` if it doesn't fit even in 128 bits.
+    pub fn product(&self) -> Option<i128> {
+        self.values
+            .iter()
+            .try_fold(1i128, |product, &v| product.checked_mul(v as i128))
+    }
+}
+
+/// Finds `k` different entries adding up to `target`, sorted by value.
+///
+/// Sorts the entries and moves two pointers inwards for the last two, so it
+/// takes O(n^(k-1)) for k >= 2.
+pub fn k_sum(expenses: &[i32], k: usize, target: i64) -> Option<Entries> {
+    let mut order: Vec<usize> = (0..expenses.len()).collect();
+    order.sort_by_key(|&i| expenses[i]);
+    let sorted: Vec<i64> = order.iter().map(|&i| expenses[i] as i64).collect();
+    let mut picked = Vec::with_capacity(k);
+    if !search(&sorted, 0, k, target, &mut picked) {
+        return None;
+    }
+    let indices = picked.iter().map(|&p| order[p]).collect();
+    Some(Entries::new(expenses, indices))
+}
+
+fn search(sorted: &[i64], start: usize, k: usize, target: i64, picked: &mut Vec<usize>) -> bool {
+    //Looks for k entries adding up to target in sorted[start..]
+    let rest = &sorted[start..];
+    if rest.len() < k {
+        return false;
+    }
+    match k {
+        0 => target == 0,
+        1 => match rest.binary_search(&target) {
+            Ok(i) => {
+                picked.push(start + i);
+                true
+            }
+            Err(_) => false,
+        },
+        2 => {
+            let (mut low, mut high) = (start, sorted.len() - 1);
+            while low < high {
+                let sum = sorted[low] + sorted[high];
+                if sum < target {
+                    low += 1;
+                } else if sum > target {
+                    high -= 1;
+                } else {
+                    picked.extend([low, high]);
+                    return true;
+                }
+            }
+            false
+        }
+        _ => {
+            //Out of reach even with the smallest or largest entries left
+            let smallest: i64 = rest[..k].iter().sum();
+            let largest: i64 = rest[rest.len() - k..].iter().sum();
+            if target < smallest || target > largest {
+                return false;
+            }
+            for first in start..=sorted.len() - k {
+                //Repeated values would only find the same sums again
+                if first > start && sorted[first] == sorted[first - 1] {
+                    continue;
+                }
+                picked.push(first);
+                let left = target.saturating_sub(sorted[first]);
+                if search(sorted, first + 1, k - 1, left, picked) {
+                    return true;
+                }
+                picked.pop();
+            }
+            false
+        }
+    }
+}
+
+/// The original search, trying every ordering of k entries. Kept to compare with.
+pub fn k_sum_permutations(expenses: &[i32], k: usize, target: i64) -> Option<Entries> {
+    (0..expenses.len())
+        .permutations(k)
+        .find(|indices| indices.iter().map(|&i| expenses[i] as i64).sum::<i64>() == target)
+        .map(|indices| Entries::new(expenses, indices))
+}
+
+/// How many times the same entry or value can be picked for a sum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reuse {
+    /// Never the same value twice, even from different entries
+    None,
+    /// Different entries, even if their values are the same
+    Duplicates,
+    /// The same entry as many times as needed
+    Entries,
+}
+
+/// Every combination of `k` entries adding up to `target`, lazily.
+///
+/// Combinations are told apart by the entries in them, except with
+/// `Reuse::None` where each set of values comes once.
+pub fn all_k_sums(expenses: &[i32], k: usize, target: i64, reuse: Reuse) -> KSums<'_> {
+    let mut order: Vec<usize> = (0..expenses.len()).collect();
+    order.sort_by_key(|&i| expenses[i]);
+    if reuse == Reuse::None {
+        order.dedup_by_key(|i| expenses[*i]);
+    }
+    let values: Vec<i64> = order.iter().map(|&i| expenses[i] as i64).collect();
+    let prefix = std::iter::once(0)
+        .chain(values.iter().scan(0, |sum, v| {
+            *sum += v;
+            Some(*sum)
+        }))
         .collect();
-    permutations
+    KSums {
+        expenses,
+        order,
+        values,
+        prefix,
+        k,
+        target,
+        reuse,
+        picked: Vec::with_capacity(k),
+        sum: 0,
+        next: Some(0),
+    }
+}
+
+pub struct KSums<'a> {
+    expenses: &'a [i32],
+    //Candidate entries sorted by value, and the sums of their prefixes
+    order: Vec<usize>,
+    values: Vec<i64>,
+    prefix: Vec<i64>,
+    k: usize,
+    target: i64,
+    reuse: Reuse,
+    //Positions in order picked so far, and the next one to try
+    picked: Vec<usize>,
+    sum: i64,
+    next: Option<usize>,
 }
 
-fn entries_sum_2020(number_of_entries: i32) -> Vec<i32> {
-    for permutation in combinations(number_of_entries) {
-        if permutation.iter().sum::<i32>() == 2020 {
-            return permutation;
+impl KSums<'_> {
+    fn backtrack(&mut self) {
+        self.next = self.picked.pop().map(|p| {
+            self.sum -= self.values[p];
+            p + 1
+        });
+    }
+
+    fn bounds(&self, p: usize, left: usize) -> Option<(i64, i64)> {
+        //Smallest and largest sums picking p and then left more
+        let n = self.values.len();
+        let value = self.values[p];
+        if self.reuse == Reuse::Entries {
+            let (left, largest) = (left as i64, self.values[n - 1]);
+            return Some((value + left * value, value + left * largest));
+        }
+        if p + left >= n {
+            return None;
+        }
+        let smallest = self.prefix[p + 1 + left] - self.prefix[p + 1];
+        let largest = self.prefix[n] - self.prefix[n - left];
+        Some((value + smallest, value + largest))
+    }
+}
+
+impl Iterator for KSums<'_> {
+    type Item = Entries;
+
+    fn next(&mut self) -> Option<Entries> {
+        loop {
+            let p = self.next?;
+            if self.picked.len() == self.k {
+                let found = self.sum == self.target;
+                let indices = self.picked.iter().map(|&p| self.order[p]).collect();
+                self.backtrack();
+                if found {
+                    return Some(Entries::new(self.expenses, indices));
+                }
+                continue;
+            }
+            if p >= self.values.len() {
+                self.backtrack();
+                continue;
+            }
+            let left = self.k - self.picked.len() - 1;
+            match self.bounds(p, left) {
+                //Later entries are bigger, so they would overshoot too
+                None => self.backtrack(),
+                Some((smallest, _)) if self.sum + smallest > self.target => self.backtrack(),
+                Some((_, largest)) if self.sum + largest < self.target => self.next = Some(p + 1),
+                Some(_) => {
+                    self.picked.push(p);
+                    self.sum += self.values[p];
+                    self.next = Some(match self.reuse {
+                        Reuse::Entries => p,
+                        _ => p + 1,
+                    });
+                }
+            }
         }
     }
-    return vec![0];
+}
+
+fn entries_sum_2020(expenses: &[i32], k: usize) -> Result<i128> {
+    let entries = k_sum(expenses, k, 2020)
+        .ok_or_else(|| AocError::no_solution(format!("no {} entries add up to 2020", k)))?;
+    entries
+        .product()
+        .ok_or_else(|| AocError::no_solution("the product doesn't fit in 128 bits"))
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: u8 = 1;
+    type Parsed<'a> = Vec<i32>;
+    type Answer1 = i128;
+    type Answer2 = i128;
+
+    fn parse(input: &str) -> Result<Vec<i32>> {
+        get_input(input)
+    }
+
+    fn part1(expenses: &Vec<i32>) -> Result<i128> {
+        entries_sum_2020(expenses, 2)
+    }
+
+    fn part2(expenses: &Vec<i32>) -> Result<i128> {
+        entries_sum_2020(expenses, 3)
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::input::bundled;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
 
     #[test]
     fn part1() {
-        let product: i32 = entries_sum_2020(2).iter().product();
-        assert_eq!(product, 1005459)
+        let expenses = get_input(&bundled("day1.txt")).unwrap();
+        assert_eq!(entries_sum_2020(&expenses, 2).unwrap(), 1005459)
     }
 
     #[test]
     fn part2() {
-        let product: i32 = entries_sum_2020(3).iter().product();
-        assert_eq!(product, 92643264)
+        let expenses = get_input(&bundled("day1.txt")).unwrap();
+        assert_eq!(entries_sum_2020(&expenses, 3).unwrap(), 92643264)
+    }
+
+    #[test]
+    fn more_entries() {
+        let expenses = get_input(&bundled("day1.txt")).unwrap();
+        let target = (expenses[3] + expenses[50] + expenses[97] + expenses[180]) as i64;
+        let entries = k_sum(&expenses, 4, target).unwrap();
+        assert_eq!(entries.indices.iter().unique().count(), 4);
+        assert_eq!(
+            entries.values.iter().map(|&v| v as i64).sum::<i64>(),
+            target
+        );
+        assert_eq!(k_sum(&expenses, 1, 2020), None);
+        assert_eq!(k_sum(&[1010], 2, 2020), None);
+        let entries = k_sum(&[1010, 5, 1010], 2, 2020).unwrap();
+        assert_eq!(entries.indices, vec![0, 2]);
+        assert!(entries_sum_2020(&[1, 2, 3], 3).is_err());
+    }
+
+    #[test]
+    fn every_combination() {
+        let example = [1721, 979, 366, 299, 675, 1456];
+        let pairs: Vec<_> = all_k_sums(&example, 2, 2020, Reuse::Duplicates).collect();
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(pairs[0].values, vec![299, 1721]);
+        assert_eq!(pairs[0].indices, vec![3, 0]);
+        let triples: Vec<_> = all_k_sums(&example, 3, 2020, Reuse::Duplicates).collect();
+        assert_eq!(triples[0].product(), Some(241861950));
+
+        let halves = [1010, 1010, 1010];
+        let count = |reuse| all_k_sums(&halves, 2, 2020, reuse).count();
+        assert_eq!(count(Reuse::None), 0);
+        assert_eq!(count(Reuse::Duplicates), 3);
+        assert_eq!(count(Reuse::Entries), 6);
+        assert_eq!(all_k_sums(&[2020], 1, 2020, Reuse::None).count(), 1);
+        assert_eq!(all_k_sums(&[], 0, 0, Reuse::None).count(), 1);
+
+        let big = k_sum(&[i32::MAX, i32::MAX, 2], 2, i32::MAX as i64 * 2).unwrap();
+        assert_eq!(big.product(), Some(i32::MAX as i128 * i32::MAX as i128));
+        let huge = Entries::new(&[i32::MIN; 5], vec![0; 5]);
+        assert_eq!(huge.product(), None);
+    }
+
+    fn brute_force(expenses: &[i32], k: usize, target: i64, reuse: Reuse) -> usize {
+        let sums = |indices: &Vec<usize>| {
+            indices.iter().map(|&i| expenses[i] as i64).sum::<i64>() == target
+        };
+        let indices = 0..expenses.len();
+        match reuse {
+            Reuse::None => indices
+                .combinations(k)
+                .filter(sums)
+                .map(|c| c.iter().map(|&i| expenses[i]).sorted().collect::<Vec<_>>())
+                .filter(|values| values.iter().all_unique())
+                .unique()
+                .count(),
+            Reuse::Duplicates => indices.combinations(k).filter(sums).count(),
+            Reuse::Entries => indices
+                .combinations_with_replacement(k)
+                .filter(sums)
+                .count(),
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn counts_every_combination(
+            expenses in vec(-20..20i32, 0..9),
+            k in 0..4usize,
+            target in -50..50i64,
+            reuse in prop_oneof![Just(Reuse::None), Just(Reuse::Duplicates), Just(Reuse::Entries)],
+        ) {
+            let found: Vec<_> = all_k_sums(&expenses, k, target, reuse).collect();
+            prop_assert_eq!(found.len(), brute_force(&expenses, k, target, reuse));
+            for entries in found {
+                prop_assert_eq!(entries.values.iter().map(|&v| v as i64).sum::<i64>(), target);
+            }
+        }
+
+
+        #[test]
+        fn agrees_with_permutations(
+            expenses in vec(-50..50i32, 0..12),
+            k in 0..5usize,
+            target in -120..120i64,
+        ) {
+            let found = k_sum(&expenses, k, target);
+            prop_assert_eq!(found.is_some(), k_sum_permutations(&expenses, k, target).is_some());
+            if let Some(entries) = found {
+                prop_assert_eq!(entries.indices.iter().unique().count(), k);
+                prop_assert_eq!(entries.values.iter().map(|&v| v as i64).sum::<i64>(), target);
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_entry() {
+        let error = get_input("1721\n97g\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 2, column 1: invalid number `97g`");
     }
 }
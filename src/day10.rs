@@ -1,12 +1,16 @@
+use crate::error::Result;
+use crate::parse::numbers;
+use crate::Solution;
 use itertools::Itertools;
+use std::iter;
 
-fn parse_input(input: &str) -> Vec<usize> {
-    let mut v: Vec<usize> = input.lines().map(|line| line.parse().unwrap()).collect();
+fn parse_input(input: &str) -> Result<Vec<usize>> {
+    let mut v: Vec<usize> = numbers(input)?;
     v.sort_unstable();
-    v
+    Ok(v)
 }
 
-fn diffs(joltages: Vec<usize>) -> (usize, usize) {
+fn diffs(joltages: &[usize]) -> (usize, usize) {
     let (ones, threes) = joltages
         .windows(2)
         .map(|w| w[1] - w[0])
@@ -60,9 +64,9 @@ impl Iterator for Fibonacci {
     }
 }
 
-fn arrangements(mut joltages: Vec<usize>) -> usize {
-    joltages.push(0);
-    joltages.rotate_right(1);
+fn arrangements(joltages: &[usize]) -> usize {
+    //The charging outlet goes first
+    let joltages: Vec<usize> = iter::once(0).chain(joltages.iter().copied()).collect();
     joltages
         .windows(3)
         .group_by(|w| w[2] - w[0] <= 3)
@@ -72,50 +76,79 @@ fn arrangements(mut joltages: Vec<usize>) -> usize {
         .product()
 }
 
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+    type Parsed<'a> = Vec<usize>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Vec<usize>> {
+        parse_input(input)
+    }
+
+    fn part1(joltages: &Vec<usize>) -> Result<usize> {
+        let (ones, threes) = diffs(joltages);
+        Ok(ones * threes)
+    }
+
+    fn part2(joltages: &Vec<usize>) -> Result<usize> {
+        Ok(arrangements(joltages))
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::input::bundled;
 
     #[test]
     fn example1_part1() {
-        let input = include_str!("../inputs/day10_example1.txt");
-        let joltages = parse_input(input);
-        assert_eq!(diffs(joltages), (7, 5));
+        let input = &bundled("day10_example1.txt");
+        let joltages = parse_input(input).unwrap();
+        assert_eq!(diffs(&joltages), (7, 5));
     }
 
     #[test]
     fn example2_part1() {
-        let input = include_str!("../inputs/day10_example2.txt");
-        let joltages = parse_input(input);
-        assert_eq!(diffs(joltages), (22, 10));
+        let input = &bundled("day10_example2.txt");
+        let joltages = parse_input(input).unwrap();
+        assert_eq!(diffs(&joltages), (22, 10));
     }
 
     #[test]
     fn part1() {
-        let input = include_str!("../inputs/day10.txt");
-        let joltages = parse_input(input);
-        assert_eq!(diffs(joltages), (65, 29));
+        let input = &bundled("day10.txt");
+        let joltages = parse_input(input).unwrap();
+        assert_eq!(diffs(&joltages), (65, 29));
         //65 * 29 = 1885
     }
 
     #[test]
     fn example1_part2() {
-        let input = include_str!("../inputs/day10_example1.txt");
-        let joltages = parse_input(input);
-        assert_eq!(arrangements(joltages), 8);
+        let input = &bundled("day10_example1.txt");
+        let joltages = parse_input(input).unwrap();
+        assert_eq!(arrangements(&joltages), 8);
     }
 
     #[test]
     fn example2_part2() {
-        let input = include_str!("../inputs/day10_example2.txt");
-        let joltages = parse_input(input);
-        assert_eq!(arrangements(joltages), 19208);
+        let input = &bundled("day10_example2.txt");
+        let joltages = parse_input(input).unwrap();
+        assert_eq!(arrangements(&joltages), 19208);
     }
 
     #[test]
     fn part2() {
-        let input = include_str!("../inputs/day10.txt");
-        let joltages = parse_input(input);
-        assert_eq!(arrangements(joltages), 2024782584832);
+        let input = &bundled("day10.txt");
+        let joltages = parse_input(input).unwrap();
+        assert_eq!(arrangements(&joltages), 2024782584832);
+    }
+
+    #[test]
+    fn malformed_joltages() {
+        let error = parse_input("16\n10\n15\n5 1").unwrap_err();
+        assert_eq!(error.to_string(), "line 4, column 1: invalid number `5 1`");
     }
 }
@@ -1,4 +1,7 @@
-use std::{collections::HashMap, num::ParseIntError, str::FromStr};
+use crate::error::{AocError, Result};
+use crate::grid::{Grid, Point, ADJACENT};
+use crate::Solution;
+use std::str::FromStr;
 
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
 enum Cell {
@@ -49,130 +52,56 @@ impl Cell {
     }
 }
 
-#[derive(PartialEq, Eq, Hash, Clone, Debug)]
-struct Position {
-    x: i32,
-    y: i32,
+#[derive(Clone)]
+pub struct Seats {
+    grid: Grid<Cell>,
 }
 
-impl Position {
-    fn new(x: i32, y: i32) -> Self {
-        Position { x, y }
-    }
-
-    fn adjacent_cells(&self) -> Vec<Position> {
-        vec![
-            Position::new(self.x - 1, self.y - 1),
-            Position::new(self.x, self.y - 1),
-            Position::new(self.x + 1, self.y - 1),
-            Position::new(self.x - 1, self.y),
-            Position::new(self.x + 1, self.y),
-            Position::new(self.x - 1, self.y + 1),
-            Position::new(self.x, self.y + 1),
-            Position::new(self.x + 1, self.y + 1),
-        ]
-    }
-}
-
-struct Grid {
-    cells: HashMap<Position, Cell>,
-}
-
-impl FromStr for Grid {
-    type Err = ParseIntError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let cells: HashMap<Position, Cell> = s
-            .lines()
-            .enumerate()
-            .flat_map(|(i, line)| {
-                line.chars().enumerate().map(move |(j, c)| {
-                    (
-                        Position::new(j as i32, i as i32),
-                        match c {
-                            'L' => Cell::EmptySeat,
-                            '.' => Cell::Floor,
-                            '#' => Cell::OccupiedSeat,
-                            _ => unreachable!(),
-                        },
-                    )
-                })
-            })
-            .collect();
+impl FromStr for Seats {
+    type Err = AocError;
 
-        Ok(Grid { cells })
+    fn from_str(s: &str) -> Result<Self> {
+        let legend = [
+            ('L', Cell::EmptySeat),
+            ('.', Cell::Floor),
+            ('#', Cell::OccupiedSeat),
+        ];
+        Ok(Seats {
+            grid: Grid::parse(s, &legend)?,
+        })
     }
 }
 
-impl Grid {
-    fn adjacent_occupied_seats(&self, source_seat: &Position) -> usize {
+impl Seats {
+    fn adjacent_occupied_seats(&self, source_seat: Point) -> usize {
         /*Given the position of a seat, returns the number of adjacent
         occupied seats */
-        source_seat
-            .adjacent_cells()
-            .iter()
-            .filter_map(|p| self.cells.get(p))
-            .filter(|c| match c {
-                Cell::OccupiedSeat => true,
-                _ => false,
-            })
+        self.grid
+            .neighbours8(source_seat)
+            .filter(|(_position, cell)| matches!(cell, Cell::OccupiedSeat))
             .count()
     }
 
-    fn visible_occupied_seats(&self, source_seat: &Position) -> usize {
+    fn visible_occupied_seats(&self, source_seat: Point) -> usize {
         /*Given the position of a seat, returns the number of visible
-        occupied seats */
-        let directions = vec![
-            (-1, -1),
-            (0, -1),
-            (1, -1),
-            (-1, 0),
-            (1, 0),
-            (-1, 1),
-            (0, 1),
-            (1, 1),
-        ];
-        //Ugly and unreadable, abuse of iterators
-        directions
+        occupied seats, the first seat seen in each direction */
+        ADJACENT
             .iter()
-            .map(|direction| {
-                (1..)
-                    .map(move |distance| {
-                        self.cells.get(&Position {
-                            x: source_seat.x + distance * direction.0,
-                            y: source_seat.y + distance * direction.1,
-                        })
-                    })
-                    .take_while(|cell| cell.is_some())
-                    .find(|cell| {
-                        cell.is_some()
-                            && match cell.unwrap() {
-                                Cell::Floor => false,
-                                _ => true,
-                            }
-                    })
-            })
-            .filter(|cell| {
-                if let Some(Some(c)) = cell {
-                    match c {
-                        Cell::OccupiedSeat => true,
-                        _ => false,
-                    }
-                } else {
-                    false
-                }
+            .filter_map(|&direction| {
+                self.grid
+                    .ray(source_seat, direction)
+                    .map(|(_position, cell)| cell)
+                    .find(|cell| !matches!(cell, Cell::Floor))
             })
+            .filter(|cell| matches!(cell, Cell::OccupiedSeat))
             .count()
     }
 
     fn count_occupied_seats(&self) -> usize {
         /*Return the total number of occupied seats in the grid */
-        self.cells
+        self.grid
             .iter()
-            .filter(|(_position, cell)| match cell {
-                Cell::OccupiedSeat => true,
-                _ => false,
-            })
+            .filter(|(_position, cell)| matches!(cell, Cell::OccupiedSeat))
             .count()
     }
 
@@ -184,18 +113,14 @@ impl Grid {
             Otherwise, the seat's state does not change.
 
         */
-        let mut stabilized = false;
-        let mut next_grid;
-        while !stabilized {
-            next_grid = self.cells.clone();
-            self.cells = next_grid
-                .iter()
-                .map(|(position, cell)| {
-                    let total = self.adjacent_occupied_seats(position);
-                    (position.clone(), cell.transition_adjacents(total))
-                })
-                .collect();
-            stabilized = next_grid == self.cells;
+        loop {
+            let next_grid = self.grid.map(|position, cell| {
+                cell.transition_adjacents(self.adjacent_occupied_seats(position))
+            });
+            if next_grid == self.grid {
+                break;
+            }
+            self.grid = next_grid;
         }
     }
 
@@ -209,55 +134,86 @@ impl Grid {
             Otherwise, the seat's state does not change.
 
         */
-        let mut stabilized = false;
-        let mut next_grid;
-        while !stabilized {
-            next_grid = self.cells.clone();
-            self.cells = next_grid
-                .iter()
-                .map(|(position, cell)| {
-                    let total = self.visible_occupied_seats(position);
-                    (position.clone(), cell.transition_visibles(total))
-                })
-                .collect();
-            stabilized = next_grid == self.cells;
+        loop {
+            let next_grid = self.grid.map(|position, cell| {
+                cell.transition_visibles(self.visible_occupied_seats(position))
+            });
+            if next_grid == self.grid {
+                break;
+            }
+            self.grid = next_grid;
         }
     }
 }
 
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+    type Parsed<'a> = Seats;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Seats> {
+        Seats::from_str(input)
+    }
+
+    fn part1(seats: &Seats) -> Result<usize> {
+        let mut grid = seats.clone();
+        grid.apply_rules();
+        Ok(grid.count_occupied_seats())
+    }
+
+    fn part2(seats: &Seats) -> Result<usize> {
+        let mut grid = seats.clone();
+        grid.apply_vision_rules();
+        Ok(grid.count_occupied_seats())
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::input::bundled;
 
     #[test]
     fn example1_part1() {
-        let input = include_str!("../inputs/day11_example1.txt");
-        let mut grid = Grid::from_str(input).unwrap();
+        let input = &bundled("day11_example1.txt");
+        let mut grid = Seats::from_str(input).unwrap();
         grid.apply_rules();
         assert_eq!(grid.count_occupied_seats(), 37);
     }
 
     #[test]
     pub fn part1() {
-        let input = include_str!("../inputs/day11.txt");
-        let mut grid = Grid::from_str(input).unwrap();
+        let input = &bundled("day11.txt");
+        let mut grid = Seats::from_str(input).unwrap();
         grid.apply_rules();
         assert_eq!(grid.count_occupied_seats(), 2368);
     }
 
     #[test]
     fn example1_part2() {
-        let input = include_str!("../inputs/day11_example1.txt");
-        let mut grid = Grid::from_str(input).unwrap();
+        let input = &bundled("day11_example1.txt");
+        let mut grid = Seats::from_str(input).unwrap();
         grid.apply_vision_rules();
         assert_eq!(grid.count_occupied_seats(), 26);
     }
 
     #[test]
     fn part2() {
-        let input = include_str!("../inputs/day11.txt");
-        let mut grid = Grid::from_str(input).unwrap();
+        let input = &bundled("day11.txt");
+        let mut grid = Seats::from_str(input).unwrap();
         grid.apply_vision_rules();
         assert_eq!(grid.count_occupied_seats(), 2124);
     }
+
+    #[test]
+    fn malformed_grid() {
+        let error = Seats::from_str("L.LL\nLL?L").err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 3: expected `L`, `.` or `#`, found `?`"
+        );
+    }
 }
@@ -1,103 +1,137 @@
-fn navigation_system(input: &str) -> u32 {
-    let mut compass = ["N", "W", "S", "E"];
-    let (x, y) = input
-        .lines()
+use crate::error::Result;
+use crate::grid::Point;
+use crate::parse::lines;
+use crate::Solution;
+
+fn instructions(input: &str) -> Result<Vec<(&str, i64)>> {
+    lines(input)
         .map(|line| {
-            let (instr, value) = line.split_at(1);
-            let value: i32 = value.parse().unwrap();
-            (instr, value)
-        })
-        .fold((0, 0), |(x, y), (mut instr, value)| {
-            if instr == "F" {
-                instr = compass.last().unwrap();
+            let action_len = line.text.chars().next().map_or(0, char::len_utf8);
+            let (instr, value) = line.text.split_at(action_len);
+            if !["N", "S", "E", "W", "L", "R", "F"].contains(&instr) {
+                return Err(line.error(instr, format!("unknown action `{}`", instr)));
             }
-            match instr {
-                "N" => (x, y + value),
-                "S" => (x, y - value),
-                "E" => (x + value, y),
-                "W" => (x - value, y),
-                "L" => {
-                    compass.rotate_left(value as usize % 360 / 90);
-                    (x, y)
-                }
-                "R" => {
-                    compass.rotate_right(value as usize % 360 / 90);
-                    (x, y)
-                }
-                _ => unreachable!(),
+            let value: i64 = line.parse(value)?;
+            //Turns are always right angles
+            if ["L", "R"].contains(&instr) && (value < 0 || value % 90 != 0) {
+                let message = format!("expected a multiple of 90 degrees, found {}", value);
+                return Err(line.error(&line.text[action_len..], message));
             }
-        });
+            Ok((instr, value))
+        })
+        .collect()
+}
+
+fn direction(action: &str) -> Point {
+    match action {
+        "N" => Point::NORTH,
+        "S" => Point::SOUTH,
+        "E" => Point::EAST,
+        "W" => Point::WEST,
+        _ => unreachable!(),
+    }
+}
+
+fn turn(mut heading: Point, action: &str, degrees: i64) -> Point {
+    for _ in 0..degrees % 360 / 90 {
+        heading = match action {
+            "L" => heading.turn_left(),
+            _ => heading.turn_right(),
+        };
+    }
+    heading
+}
+
+fn navigation_system(instructions: &[(&str, i64)]) -> u64 {
+    let mut ship = Point::ORIGIN;
+    let mut heading = Point::EAST;
+    for &(instr, value) in instructions {
+        match instr {
+            "L" | "R" => heading = turn(heading, instr, value),
+            "F" => ship += heading * value,
+            _ => ship += direction(instr) * value,
+        }
+    }
     //Manhattan distance
-    (x.abs() + y.abs()) as u32
+    ship.manhattan()
 }
 
-fn waypoint_system(input: &str) -> u32 {
-    let compass = [(-1, 1), (-1, -1), (1, -1)];
-    let (x, y, _waypoint_x, _waypoint_y) = input
-        .lines()
-        .map(|line| {
-            let (instr, value) = line.split_at(1);
-            let value: i32 = value.parse().unwrap();
-            (instr, value)
-        })
-        .fold((0, 0, 10, 1), |(x, y, wp_x, wp_y), (instr, value)| {
-            //wp means waypoint
-            match instr {
-                "N" => (x, y, wp_x, wp_y + value),
-                "S" => (x, y, wp_x, wp_y - value),
-                "E" => (x, y, wp_x + value, wp_y),
-                "W" => (x, y, wp_x - value, wp_y),
-                "L" => {
-                    let direction = compass[(value as usize % 360 / 90) - 1];
-                    if (value % 360 / 90) % 2 != 0 {
-                        //Swap wp_x with wp_y
-                        (x, y, wp_y * direction.0, wp_x * direction.1)
-                    } else {
-                        (x, y, wp_x * direction.0, wp_y * direction.1)
-                    }
-                }
-                "R" => {
-                    let direction = compass[compass.len() - (value as usize % 360 / 90)];
-                    if (value % 360 / 90) % 2 != 0 {
-                        //Swap wp_x with wp_y
-                        (x, y, wp_y * direction.0, wp_x * direction.1)
-                    } else {
-                        (x, y, wp_x * direction.0, wp_y * direction.1)
-                    }
-                }
-                "F" => (x + value * wp_x, y + value * wp_y, wp_x, wp_y),
-                _ => unreachable!(),
-            }
-        });
+fn waypoint_system(instructions: &[(&str, i64)]) -> u64 {
+    let mut ship = Point::ORIGIN;
+    //Relative to the ship
+    let mut waypoint = Point::new(10, -1);
+    for &(instr, value) in instructions {
+        match instr {
+            "L" | "R" => waypoint = turn(waypoint, instr, value),
+            "F" => ship += waypoint * value,
+            _ => waypoint += direction(instr) * value,
+        }
+    }
     //Manhattan distance
-    (x.abs() + y.abs()) as u32
+    ship.manhattan()
+}
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+    type Parsed<'a> = Vec<(&'a str, i64)>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Vec<(&str, i64)>> {
+        instructions(input)
+    }
+
+    fn part1(instructions: &Vec<(&str, i64)>) -> Result<u64> {
+        Ok(navigation_system(instructions))
+    }
+
+    fn part2(instructions: &Vec<(&str, i64)>) -> Result<u64> {
+        Ok(waypoint_system(instructions))
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::input::bundled;
 
     #[test]
     fn example1_part1() {
-        let input = include_str!("../inputs/day12_example1.txt");
-        assert_eq!(navigation_system(input), 25);
+        let input = &bundled("day12_example1.txt");
+        assert_eq!(navigation_system(&instructions(input).unwrap()), 25);
     }
 
     #[test]
     fn part1() {
-        let input = include_str!("../inputs/day12.txt");
-        assert_eq!(navigation_system(input), 2847);
+        let input = &bundled("day12.txt");
+        assert_eq!(navigation_system(&instructions(input).unwrap()), 2847);
     }
 
     #[test]
     fn example1_part2() {
-        let input = include_str!("../inputs/day12_example1.txt");
-        assert_eq!(waypoint_system(input), 286);
+        let input = &bundled("day12_example1.txt");
+        assert_eq!(waypoint_system(&instructions(input).unwrap()), 286);
     }
 
     #[test]
     fn part2() {
-        let input = include_str!("../inputs/day12.txt");
-        assert_eq!(waypoint_system(input), 29839);
+        let input = &bundled("day12.txt");
+        assert_eq!(waypoint_system(&instructions(input).unwrap()), 29839);
+    }
+
+    #[test]
+    fn malformed_instructions() {
+        let error = instructions("F10\nN3\nX7").unwrap_err();
+        assert_eq!(error.to_string(), "line 3, column 1: unknown action `X`");
+        let error = instructions("F10\nR45").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 2: expected a multiple of 90 degrees, found 45"
+        );
+        let error = instructions("F").unwrap_err();
+        assert_eq!(error.to_string(), "line 1, column 2: invalid number ``");
+        assert_eq!(waypoint_system(&instructions("L360\nF1").unwrap()), 11);
     }
 }
@@ -1,36 +1,61 @@
-fn catch_bus(input: &str) -> (usize, usize) {
-    //Returns a tuple with the bus ID and the number of minutes to wait
-    let mut lines = input.lines();
-    let earliest: usize = lines.next().unwrap().parse().unwrap();
-    let bus_ids: Vec<usize> = lines
+use crate::error::{AocError, Result};
+use crate::parse::lines;
+use crate::Solution;
+
+#[derive(Debug)]
+pub struct Notes {
+    earliest: usize,
+    //Pairs of (delays, bus_ids)
+    buses: Vec<(usize, usize)>,
+}
+
+fn parse_notes(input: &str) -> Result<Notes> {
+    let first = lines(input)
         .next()
-        .unwrap()
-        .split(',')
-        .filter_map(|n| n.parse::<usize>().ok())
-        .collect();
-    bus_ids
+        .ok_or_else(|| AocError::parse(1, 1, "expected the earliest timestamp"))?;
+    Ok(Notes {
+        earliest: first.parse(first.text)?,
+        buses: delay_buses(input)?,
+    })
+}
+
+fn catch_bus(notes: &Notes) -> Result<(usize, usize)> {
+    //Returns a tuple with the bus ID and the number of minutes to wait
+    notes
+        .buses
         .iter()
-        .map(|bus_id| (*bus_id, bus_id - (earliest % bus_id)))
+        .map(|(_delay, bus_id)| (*bus_id, bus_id - (notes.earliest % bus_id)))
         .min_by(|x, y| x.1.cmp(&y.1))
-        .unwrap()
+        .ok_or_else(|| AocError::no_solution("there are no buses in service"))
 }
 
-fn delay_buses(input: &str) -> Vec<(usize, usize)> {
-    //Pairs of (delays, bus_ids)
-    input
-        .lines()
+fn delay_buses(input: &str) -> Result<Vec<(usize, usize)>> {
+    //Pairs of (delays, bus_ids), from the last line of the notes
+    let line = lines(input)
         .last()
-        .unwrap()
+        .ok_or_else(|| AocError::parse(1, 1, "expected the bus ids"))?;
+    line.text
         .split(',')
         .enumerate()
         .filter(|(_i, n)| *n != "x")
-        .map(|(i, n)| (i, n.parse::<usize>().unwrap()))
+        .map(|(i, n)| match line.parse::<usize>(n)? {
+            0 => Err(line.error(n, "bus ids start at 1")),
+            bus_id => Ok((i, bus_id)),
+        })
         .collect()
 }
 
-fn subsequent_timestamp(input: &str) -> usize {
-    //Pairs of (delays, bus_ids)
-    let delays_buses: Vec<(usize, usize)> = delay_buses(input);
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+//Brute-force version, only fast enough for the examples
+#[cfg(test)]
+fn subsequent_timestamp(delays_buses: &[(usize, usize)]) -> usize {
     //It has to be multiple of the first bus_id
     let first = delays_buses[0].1;
     (first..)
@@ -44,7 +69,7 @@ fn subsequent_timestamp(input: &str) -> usize {
         .unwrap()
 }
 
-fn improved_subsequent_timestamp(input: &str) -> usize {
+fn improved_subsequent_timestamp(delays_buses: &[(usize, usize)]) -> usize {
     /*The above function works well for smalls series, since the number
     of combinations grows up exponentially. So brute-force it's unfeasible
     for the last test.
@@ -53,9 +78,6 @@ fn improved_subsequent_timestamp(input: &str) -> usize {
     https://en.wikipedia.org/wiki/Chinese_remainder_theorem#Search_by_sieving
     This is quite similar to what I have done, and I just adapted the above solution
     to follow these rules and make the steps bigger*/
-
-    //Pairs of (delays, bus_ids)
-    let delays_buses: Vec<(usize, usize)> = delay_buses(input);
     let (starting_point, _step) =
         delays_buses
             .iter()
@@ -70,63 +92,140 @@ fn improved_subsequent_timestamp(input: &str) -> usize {
     starting_point
 }
 
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u8 = 13;
+    type Parsed<'a> = Notes;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Notes> {
+        parse_notes(input)
+    }
+
+    fn part1(notes: &Notes) -> Result<usize> {
+        let (bus_id, wait) = catch_bus(notes)?;
+        Ok(bus_id * wait)
+    }
+
+    fn part2(notes: &Notes) -> Result<usize> {
+        //Sieving only ends when the ids have no common factors
+        for (i, (_, a)) in notes.buses.iter().enumerate() {
+            if notes.buses[i + 1..].iter().any(|(_, b)| gcd(*a, *b) != 1) {
+                return Err(AocError::no_solution("bus ids must be coprime"));
+            }
+        }
+        Ok(improved_subsequent_timestamp(&notes.buses))
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::input::bundled;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use proptest::sample::subsequence;
+
+    //Small prime ids, so brute force ends quickly, the first one leaving at 0
+    fn buses() -> impl Strategy<Value = Vec<(usize, usize)>> {
+        let ids = subsequence(vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31], 1..=4).prop_shuffle();
+        (ids, vec(0..4usize, 4)).prop_map(|(ids, gaps)| {
+            let delays = gaps.iter().scan(0, |delay, gap| {
+                let current = *delay;
+                *delay += 1 + gap;
+                Some(current)
+            });
+            delays.zip(ids).collect()
+        })
+    }
 
     #[test]
     fn example1_part1() {
-        let input = include_str!("../inputs/day13_example1.txt");
-        let tuple = catch_bus(input);
+        let input = &bundled("day13_example1.txt");
+        let tuple = catch_bus(&parse_notes(input).unwrap()).unwrap();
         assert_eq!(tuple.0 * tuple.1, 295);
     }
 
     #[test]
     fn part1() {
-        let input = include_str!("../inputs/day13.txt");
-        let tuple = catch_bus(input);
+        let input = &bundled("day13.txt");
+        let tuple = catch_bus(&parse_notes(input).unwrap()).unwrap();
         assert_eq!(tuple.0 * tuple.1, 3606);
     }
 
     #[test]
     fn example1_part2() {
-        let input = include_str!("../inputs/day13_example1.txt");
-        assert_eq!(subsequent_timestamp(input), 1068781);
+        let input = &bundled("day13_example1.txt");
+        assert_eq!(subsequent_timestamp(&delay_buses(input).unwrap()), 1068781);
     }
 
     #[test]
     fn example2_part2() {
         let input = "17,x,13,19";
-        assert_eq!(subsequent_timestamp(input), 3417);
+        assert_eq!(subsequent_timestamp(&delay_buses(input).unwrap()), 3417);
     }
 
     #[test]
     fn example3_part2() {
         let input = "67,7,59,61";
-        assert_eq!(subsequent_timestamp(input), 754018);
+        assert_eq!(subsequent_timestamp(&delay_buses(input).unwrap()), 754018);
     }
 
     #[test]
     fn example4_part2() {
         let input = "67,x,7,59,61";
-        assert_eq!(subsequent_timestamp(input), 779210);
+        assert_eq!(subsequent_timestamp(&delay_buses(input).unwrap()), 779210);
     }
 
     #[test]
     fn example5_part2() {
         let input = "67,7,x,59,61";
-        assert_eq!(subsequent_timestamp(input), 1261476);
+        assert_eq!(subsequent_timestamp(&delay_buses(input).unwrap()), 1261476);
     }
 
     #[test]
     fn example6_part2() {
         let input = "1789,37,47,1889";
-        assert_eq!(subsequent_timestamp(input), 1202161486);
+        assert_eq!(
+            subsequent_timestamp(&delay_buses(input).unwrap()),
+            1202161486
+        );
     }
 
     #[test]
     fn part2() {
-        let input = include_str!("../inputs/day13.txt");
-        assert_eq!(improved_subsequent_timestamp(input), 379786358533423);
+        let input = &bundled("day13.txt");
+        assert_eq!(
+            improved_subsequent_timestamp(&delay_buses(input).unwrap()),
+            379786358533423
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn sieving_agrees_with_brute_force(buses in buses()) {
+            //Brute force starts looking after 0
+            prop_assume!(buses.iter().any(|(delay, id)| delay % id != 0));
+            prop_assert_eq!(
+                improved_subsequent_timestamp(&buses),
+                subsequent_timestamp(&buses)
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_notes() {
+        let error = parse_notes("939\n7,13,x,x,y").unwrap_err();
+        assert_eq!(error.to_string(), "line 2, column 10: invalid number `y`");
+        let error = parse_notes("939\n7,0").unwrap_err();
+        assert_eq!(error.to_string(), "line 2, column 3: bus ids start at 1");
+        let error = parse_notes("").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 1: expected the earliest timestamp"
+        );
+        assert!(Day13::part2(&parse_notes("0\n4,x,6").unwrap()).is_err());
     }
 }
@@ -1,5 +1,44 @@
+use crate::error::{AocError, Result};
+use crate::parse::lines;
+use crate::Solution;
 use itertools::Itertools;
-use std::{collections::HashMap, num::ParseIntError, str::FromStr};
+use std::{collections::HashMap, str::FromStr};
+
+#[derive(Debug)]
+pub enum Instruction<'a> {
+    Mask(&'a str),
+    Write { address: u64, value: u64 },
+}
+
+fn program(input: &str) -> Result<Vec<Instruction<'_>>> {
+    lines(input)
+        .map(|line| {
+            if line.text.starts_with("mask") {
+                let [mask] = line.fields("mask = {mask}")?;
+                //Only checking the mask, each part builds its own kind
+                Mask::from_str(mask).map_err(|e| line.locate(mask, e))?;
+                return Ok(Instruction::Mask(mask));
+            }
+            let [address, value] = line.fields("mem[{address}] = {value}")?;
+            Ok(Instruction::Write {
+                address: line.parse(address)?,
+                value: line.parse(value)?,
+            })
+        })
+        .collect()
+}
+
+fn check_mask(s: &str) -> Result<()> {
+    //Masks are 36 bits long
+    if let Some((i, c)) = s.chars().enumerate().find(|(_i, c)| !"01X".contains(*c)) {
+        let message = format!("expected `0`, `1` or `X`, found `{}`", c);
+        return Err(AocError::parse(1, i + 1, message));
+    }
+    if s.len() > 36 {
+        return Err(AocError::parse(1, 37, "masks are 36 bits long"));
+    }
+    Ok(())
+}
 
 /*
     0X1  Input mask divide into 2 masks
@@ -17,9 +56,10 @@ struct Mask {
 }
 
 impl FromStr for Mask {
-    type Err = ParseIntError;
+    type Err = AocError;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    fn from_str(s: &str) -> Result<Self> {
+        check_mask(s)?;
         let (and, or) =
             s.bytes()
                 .rev()
@@ -40,30 +80,19 @@ impl Mask {
     }
 }
 
-fn sum_memory(input: &str) -> u64 {
+fn sum_memory(program: &[Instruction]) -> Result<u64> {
     let mut memory: HashMap<u64, u64> = HashMap::new();
-    let mut value: u64;
-    let mut address: u64;
     //Null mask
-    let mut mask = Mask::from_str("XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX").unwrap();
-    for line in input.lines() {
-        if line.contains("mask") {
-            mask = Mask::from_str(line.split(" = ").last().unwrap()).unwrap();
-        } else {
-            let mut line_parts = line.split(" = ");
-            value = line_parts.clone().last().unwrap().parse().unwrap();
-            address = line_parts
-                .nth(0)
-                .unwrap()
-                .split(|c| c == '[' || c == ']')
-                .nth(1)
-                .unwrap()
-                .parse()
-                .unwrap();
-            memory.insert(address, mask.apply(value));
+    let mut mask = Mask::from_str("XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX")?;
+    for instruction in program {
+        match *instruction {
+            Instruction::Mask(new_mask) => mask = Mask::from_str(new_mask)?,
+            Instruction::Write { address, value } => {
+                memory.insert(address, mask.apply(value));
+            }
         }
     }
-    memory.values().sum()
+    Ok(memory.values().sum())
 }
 
 /*
@@ -84,10 +113,11 @@ struct DecoderMask {
 }
 
 impl FromStr for DecoderMask {
-    type Err = ParseIntError;
+    type Err = AocError;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut xor = vec![];
+    fn from_str(s: &str) -> Result<Self> {
+        check_mask(s)?;
+        let xor = vec![];
         let (mut xor, or) = s
             .bytes()
             .rev()
@@ -106,7 +136,7 @@ impl FromStr for DecoderMask {
             .powerset()
             .map(|vec| vec.into_iter().sum())
             .collect();
-        Ok(DecoderMask { xor: xor, or })
+        Ok(DecoderMask { xor, or })
     }
 }
 
@@ -117,59 +147,95 @@ impl DecoderMask {
     }
 }
 
-fn sum_memory_decoder(input: &str) -> u64 {
+fn sum_memory_decoder(program: &[Instruction]) -> Result<u64> {
     let mut memory: HashMap<u64, u64> = HashMap::new();
-    let mut value: u64;
-    let mut address: u64;
     //Null mask
-    let mut mask = DecoderMask::from_str("00000000000000000000000000000000000").unwrap();
-    for line in input.lines() {
-        if line.contains("mask") {
-            mask = DecoderMask::from_str(line.split(" = ").last().unwrap()).unwrap();
-        } else {
-            let mut line_parts = line.split(" = ");
-            value = line_parts.clone().last().unwrap().parse().unwrap();
-            address = line_parts
-                .nth(0)
-                .unwrap()
-                .split(|c| c == '[' || c == ']')
-                .nth(1)
-                .unwrap()
-                .parse()
-                .unwrap();
-            for decoded_address in mask.apply(address) {
-                memory.insert(decoded_address, value);
+    let mut mask = DecoderMask::from_str("00000000000000000000000000000000000")?;
+    for instruction in program {
+        match *instruction {
+            Instruction::Mask(new_mask) => mask = DecoderMask::from_str(new_mask)?,
+            Instruction::Write { address, value } => {
+                for decoded_address in mask.apply(address) {
+                    memory.insert(decoded_address, value);
+                }
             }
         }
     }
-    memory.values().sum()
+    Ok(memory.values().sum())
+}
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+    type Parsed<'a> = Vec<Instruction<'a>>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Vec<Instruction<'_>>> {
+        program(input)
+    }
+
+    fn part1(program: &Vec<Instruction>) -> Result<u64> {
+        sum_memory(program)
+    }
+
+    fn part2(program: &Vec<Instruction>) -> Result<u64> {
+        sum_memory_decoder(program)
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::input::bundled;
 
     #[test]
     fn example1_part1() {
-        let input = include_str!("../inputs/day14_example1.txt");
-        assert_eq!(sum_memory(input), 165);
+        let input = &bundled("day14_example1.txt");
+        assert_eq!(sum_memory(&program(input).unwrap()).unwrap(), 165);
     }
 
     #[test]
     fn part1() {
-        let input = include_str!("../inputs/day14.txt");
-        assert_eq!(sum_memory(input), 5902420735773);
+        let input = &bundled("day14.txt");
+        assert_eq!(sum_memory(&program(input).unwrap()).unwrap(), 5902420735773);
     }
 
     #[test]
     fn example1_part2() {
-        let input = include_str!("../inputs/day14_example2.txt");
-        assert_eq!(sum_memory_decoder(input), 208);
+        let input = &bundled("day14_example2.txt");
+        assert_eq!(sum_memory_decoder(&program(input).unwrap()).unwrap(), 208);
     }
 
     #[test]
     fn part2() {
-        let input = include_str!("../inputs/day14.txt");
-        assert_eq!(sum_memory_decoder(input), 3801988250775);
+        let input = &bundled("day14.txt");
+        assert_eq!(
+            sum_memory_decoder(&program(input).unwrap()).unwrap(),
+            3801988250775
+        );
+    }
+
+    #[test]
+    fn malformed_program() {
+        let error =
+            program("mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X\nmem[8] = 1a").unwrap_err();
+        assert_eq!(error.to_string(), "line 2, column 10: invalid number `1a`");
+        let error = program("mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX2X").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 42: expected `0`, `1` or `X`, found `2`"
+        );
+        let error = program("mem8] = 11").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 1: expected `mem[<address>] = <value>`"
+        );
+        let error = program(&format!("mask = {}", "X".repeat(64))).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 44: masks are 36 bits long"
+        );
     }
 }
@@ -1,3 +1,6 @@
+use crate::error::{AocError, Result};
+use crate::parse::lines;
+use crate::Solution;
 use std::collections::HashMap;
 
 struct MemoryGame {
@@ -14,7 +17,7 @@ impl MemoryGame {
             .map(|(i, n)| (*n, (0_usize, i + 1)))
             .collect();
         MemoryGame {
-            numbers: numbers,
+            numbers,
             current_position: input.len() + 1,
             target: input[input.len() - 1],
         }
@@ -28,16 +31,12 @@ impl Iterator for MemoryGame {
         let age;
         let position = self.numbers.get(&self.target);
 
-        if position.is_none() {
-            age = 0;
-            let older = if let Some(old) = self.numbers.get(&age) {
-                old.1
-            } else {
-                self.current_position
-            };
-            self.numbers
-                .insert(age, (self.current_position, self.current_position));
-        } else if position.unwrap().0 == 0 {
+        if let Some(&(first, last)) = position.filter(|p| p.0 != 0) {
+            age = last - first;
+            let older = self.numbers.get(&age);
+            let older = if let Some(old) = older { old.1 } else { 0 };
+            self.numbers.insert(age, (older, self.current_position));
+        } else if position.is_some() {
             age = 0;
             let older = if let Some(old) = self.numbers.get(&age) {
                 old.1
@@ -46,11 +45,9 @@ impl Iterator for MemoryGame {
             };
             self.numbers.insert(age, (older, self.current_position));
         } else {
-            let p = position.unwrap();
-            age = p.1 - p.0;
-            let older = self.numbers.get(&age);
-            let older = if let Some(old) = older { old.1 } else { 0 };
-            self.numbers.insert(age, (older, self.current_position));
+            age = 0;
+            self.numbers
+                .insert(age, (self.current_position, self.current_position));
         }
         self.target = age;
         self.current_position += 1;
@@ -58,6 +55,43 @@ impl Iterator for MemoryGame {
     }
 }
 
+fn parse_input(input: &str) -> Result<Vec<usize>> {
+    let line = lines(input)
+        .next()
+        .ok_or_else(|| AocError::parse(1, 1, "expected the starting numbers"))?;
+    line.list(",")
+}
+
+fn spoken_at(starting_numbers: &[usize], turn: usize) -> usize {
+    if turn <= starting_numbers.len() {
+        return starting_numbers[turn - 1];
+    }
+    //The game iterator starts after the starting numbers
+    let nth = turn - starting_numbers.len() - 1;
+    MemoryGame::new(starting_numbers.to_vec()).nth(nth).unwrap()
+}
+
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+    type Parsed<'a> = Vec<usize>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Vec<usize>> {
+        parse_input(input)
+    }
+
+    fn part1(starting_numbers: &Vec<usize>) -> Result<usize> {
+        Ok(spoken_at(starting_numbers, 2020))
+    }
+
+    fn part2(starting_numbers: &Vec<usize>) -> Result<usize> {
+        Ok(spoken_at(starting_numbers, 30000000))
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -151,4 +185,12 @@ pub mod tests {
         let nth = 30000000 - length - 1;
         assert_eq!(game.into_iter().nth(nth).unwrap(), 1065);
     }
+
+    #[test]
+    fn malformed_numbers() {
+        let error = parse_input("0,3,,6").unwrap_err();
+        assert_eq!(error.to_string(), "line 1, column 5: invalid number ``");
+        assert!(parse_input("").is_err());
+        assert_eq!(spoken_at(&[0, 3, 6], 2), 3);
+    }
 }
@@ -1,92 +1,119 @@
+use crate::error::{AocError, Result};
+use crate::parse::{blocks, Line};
+use crate::Solution;
 use std::collections::HashSet;
 use std::ops::RangeInclusive;
 
-fn parse_input(input: &str) -> (&str, &str, &str) {
-    let (rules, rest) = (
-        input.split("your ticket:").nth(0).unwrap(),
-        input.split("your ticket:").nth(1).unwrap(),
-    );
-
-    let (your_ticket, nearby_tickets) = (
-        rest.split("nearby tickets:").nth(0).unwrap(),
-        rest.split("nearby tickets:").nth(1).unwrap(),
-    );
-    (rules.trim(), your_ticket.trim(), nearby_tickets.trim())
+#[derive(Debug)]
+pub struct Rule<'a> {
+    name: &'a str,
+    ranges: Vec<RangeInclusive<usize>>,
 }
 
-fn ticket_scannning_error_rate(input: &str) -> usize {
-    let (rules, _, nearby_tickets) = parse_input(input);
-    let rules: Vec<RangeInclusive<usize>> = rules
-        .lines()
-        .flat_map(|s| {
-            s.split(':').last().unwrap().split(" or ").map(|r| {
-                r.trim()
-                    .split('-')
-                    .next()
-                    .unwrap()
-                    .parse::<usize>()
-                    .unwrap()
-                    ..=r.trim()
-                        .split('-')
-                        .nth(1)
-                        .unwrap()
-                        .parse::<usize>()
-                        .unwrap()
-            })
-        })
-        .collect();
-    let sum: usize = nearby_tickets
-        .lines()
-        .flat_map(|s| s.split(',').map(|n| n.parse::<usize>().unwrap()))
-        .filter(|n| !rules.iter().any(|range| range.contains(n)))
-        .sum();
-    sum
+impl Rule<'_> {
+    fn matches(&self, n: &usize) -> bool {
+        self.ranges.iter().any(|range| range.contains(n))
+    }
 }
 
-fn who_is_who(input: &str) -> usize {
-    let (rules, your_ticket, nearby_tickets) = parse_input(input);
-    let your_ticket: Vec<usize> = your_ticket
-        .split(',')
-        .map(|s| s.parse::<usize>().unwrap())
-        .collect();
-    let rules: Vec<Vec<RangeInclusive<usize>>> = rules
-        .lines()
-        .map(|s| {
-            s.split(':')
-                .last()
-                .unwrap()
-                .split(" or ")
-                .map(|r| {
-                    r.trim()
-                        .split('-')
-                        .next()
-                        .unwrap()
-                        .parse::<usize>()
-                        .unwrap()
-                        ..=r.trim()
-                            .split('-')
-                            .nth(1)
-                            .unwrap()
-                            .parse::<usize>()
-                            .unwrap()
-                })
-                .collect::<Vec<RangeInclusive<usize>>>()
+#[derive(Debug)]
+pub struct Notes<'a> {
+    rules: Vec<Rule<'a>>,
+    your_ticket: Vec<usize>,
+    nearby_tickets: Vec<Vec<usize>>,
+}
+
+fn parse_rule(line: Line) -> Result<Rule> {
+    //departure location: 39-715 or 734-949
+    let [name, ranges] = line.fields("{field}: {ranges}")?;
+    let ranges = ranges
+        .split(" or ")
+        .map(|range| {
+            let (start, end) = range
+                .split_once('-')
+                .ok_or_else(|| line.error(range, format!("expected a range, found `{}`", range)))?;
+            Ok(line.parse(start)?..=line.parse(end)?)
         })
-        .collect();
+        .collect::<Result<_>>()?;
+    Ok(Rule { name, ranges })
+}
+
+fn parse_ticket(line: Line, fields: usize) -> Result<Vec<usize>> {
+    let ticket: Vec<usize> = line.list(",")?;
+    if ticket.len() != fields {
+        let message = format!("expected {} fields, found {}", fields, ticket.len());
+        return Err(line.error(line.text, message));
+    }
+    Ok(ticket)
+}
+
+fn section<'a>(block: Option<Vec<Line<'a>>>, header: &str, end: usize) -> Result<Vec<Line<'a>>> {
+    //The lines of a section after its header
+    let block = block.ok_or_else(|| AocError::parse(end, 1, format!("expected `{}`", header)))?;
+    match block.split_first() {
+        Some((first, tickets)) if first.text == header => Ok(tickets.to_vec()),
+        _ => Err(block[0].error(block[0].text, format!("expected `{}`", header))),
+    }
+}
+
+fn parse_input(input: &str) -> Result<Notes<'_>> {
+    //Rules, your ticket and nearby tickets, separated by blank lines
+    let end = input.lines().count() + 1;
+    let mut blocks = blocks(input);
+    let rules = blocks
+        .next()
+        .ok_or_else(|| AocError::parse(1, 1, "expected some rules"))?
+        .into_iter()
+        .map(parse_rule)
+        .collect::<Result<Vec<_>>>()?;
+    let fields = rules.len();
+    let your_ticket = match section(blocks.next(), "your ticket:", end)?[..] {
+        [line] => parse_ticket(line, fields)?,
+        _ => {
+            return Err(AocError::parse(
+                end,
+                1,
+                "expected exactly one ticket of yours",
+            ))
+        }
+    };
+    let nearby_tickets = section(blocks.next(), "nearby tickets:", end)?
+        .into_iter()
+        .map(|line| parse_ticket(line, fields))
+        .collect::<Result<_>>()?;
+    if let Some(block) = blocks.next() {
+        return Err(block[0].error(block[0].text, "expected the end of the notes"));
+    }
+    Ok(Notes {
+        rules,
+        your_ticket,
+        nearby_tickets,
+    })
+}
+
+fn ticket_scannning_error_rate(notes: &Notes) -> usize {
+    notes
+        .nearby_tickets
+        .iter()
+        .flatten()
+        .filter(|n| !notes.rules.iter().any(|rule| rule.matches(n)))
+        .sum()
+}
+
+fn your_ticket_fields<'a>(notes: &Notes<'a>) -> Result<Vec<(&'a str, usize)>> {
+    //Returns the name of each field along with its value in your ticket
+    let rules = &notes.rules;
 
     //Fields enumerated by column
-    let fields = nearby_tickets
-        .lines()
-        .flat_map(|s| {
-            s.split(',')
-                .map(|n| n.parse::<usize>().unwrap())
-                .enumerate()
-        })
-        .filter(|(_i, n)| {
-            rules
+    let fields = notes
+        .nearby_tickets
+        .iter()
+        .filter(|ticket| {
+            ticket
                 .iter()
-                .any(|rule| rule[0].contains(n) || rule[1].contains(n))
-        });
+                .all(|n| rules.iter().any(|rule| rule.matches(n)))
+        })
+        .flat_map(|ticket| ticket.iter().enumerate());
     let rules_size = rules.len();
 
     //Matches the position of the rule with the positions of the posible columns in the tickets
@@ -97,8 +124,8 @@ fn who_is_who(input: &str) -> usize {
             for column in 0..rules_size {
                 if fields
                     .clone()
-                    .filter(|(i, _field)| *i == column as usize)
-                    .all(|(_i, f)| rule[0].contains(&f) || rule[1].contains(&f))
+                    .filter(|(i, _field)| *i == column)
+                    .all(|(_i, f)| rule.matches(f))
                 {
                     v.insert(column);
                 }
@@ -106,14 +133,24 @@ fn who_is_who(input: &str) -> usize {
             v
         })
         .collect();
-    sieve(positions)
+    let columns =
+        sieve(positions).ok_or_else(|| AocError::no_solution("fields can't be told apart"))?;
+    Ok(rules
+        .iter()
+        .zip(columns)
+        .map(|(rule, p)| (rule.name, notes.your_ticket[p]))
+        .collect())
+}
+
+fn who_is_who(notes: &Notes) -> Result<usize> {
+    Ok(your_ticket_fields(notes)?
         .iter()
-        .take(6)
-        .map(|p| your_ticket[*p])
-        .product()
+        .filter(|(name, _value)| name.starts_with("departure"))
+        .map(|(_name, value)| value)
+        .product())
 }
 
-fn sieve(mut options: Vec<HashSet<usize>>) -> Vec<usize> {
+fn sieve(mut options: Vec<HashSet<usize>>) -> Option<Vec<usize>> {
     /*Input example
     [6, 7, 10, 11, 12, 15, 16, 17, 19]
     [4, 6, 7, 10, 11, 12, 14, 15, 16, 17, 18, 19]
@@ -140,43 +177,97 @@ fn sieve(mut options: Vec<HashSet<usize>>) -> Vec<usize> {
     let mut results: Vec<usize> = Vec::new();
     results.resize(size, size);
     while results.contains(&size) {
-        while let Some(i) = options.iter().position(|s| s.len() == 1) {
-            let found = *options[i].iter().next().unwrap();
-            results[i] = found;
-            //Remove the found item from the rest
-            for s in &mut options {
-                s.remove(&found);
-            }
+        //Without a rule that fits only one column we are stuck
+        let i = options.iter().position(|s| s.len() == 1)?;
+        let found = *options[i].iter().next().unwrap();
+        results[i] = found;
+        //Remove the found item from the rest
+        for s in &mut options {
+            s.remove(&found);
         }
     }
-    results
+    Some(results)
+}
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    const DAY: u8 = 16;
+    type Parsed<'a> = Notes<'a>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Notes<'_>> {
+        parse_input(input)
+    }
+
+    fn part1(notes: &Notes) -> Result<usize> {
+        Ok(ticket_scannning_error_rate(notes))
+    }
+
+    fn part2(notes: &Notes) -> Result<usize> {
+        who_is_who(notes)
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::input::bundled;
 
     #[test]
     fn example1_part1() {
-        let input = include_str!("../inputs/day16_example1.txt");
-        assert_eq!(ticket_scannning_error_rate(input), 71);
+        let input = &bundled("day16_example1.txt");
+        assert_eq!(
+            ticket_scannning_error_rate(&parse_input(input).unwrap()),
+            71
+        );
     }
 
     #[test]
     fn part1() {
-        let input = include_str!("../inputs/day16.txt");
-        assert_eq!(ticket_scannning_error_rate(input), 21956);
+        let input = &bundled("day16.txt");
+        assert_eq!(
+            ticket_scannning_error_rate(&parse_input(input).unwrap()),
+            21956
+        );
     }
 
     #[test]
     fn test2() {
-        let input = include_str!("../inputs/day16_example2.txt");
-        assert_eq!(who_is_who(input), 0);
+        let input = &bundled("day16_example2.txt");
+        let fields = your_ticket_fields(&parse_input(input).unwrap()).unwrap();
+        assert_eq!(fields, vec![("class", 12), ("row", 11), ("seat", 13)]);
     }
 
     #[test]
     fn part2() {
-        let input = include_str!("../inputs/day16.txt");
-        assert_eq!(who_is_who(input), 3709435214239);
+        let input = &bundled("day16.txt");
+        assert_eq!(
+            who_is_who(&parse_input(input).unwrap()).unwrap(),
+            3709435214239
+        );
+    }
+
+    #[test]
+    fn malformed_notes() {
+        let error = parse_input("class: 1-3 or 5-7\nrow: 6-11 or 33\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 14: expected a range, found `33`"
+        );
+        let error = parse_input("class: 1-3 or 5-7\n\nyour ticket:\n7,1").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 4, column 1: expected 1 fields, found 2"
+        );
+        let error = parse_input("class: 1-3 or 5-7\n\nyour ticket:\n7").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 5, column 1: expected `nearby tickets:`"
+        );
+        let notes =
+            parse_input("a: 1-3\nb: 1-3\n\nyour ticket:\n1,2\n\nnearby tickets:\n2,1").unwrap();
+        assert!(who_is_who(&notes).is_err());
     }
 }
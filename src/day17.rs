@@ -1,10 +1,12 @@
+use crate::error::{AocError, Result};
+use crate::parse::lines;
+use crate::Solution;
 use itertools::Itertools;
 use std::collections::HashSet;
-use std::iter;
 use std::ops::RangeInclusive;
-use std::{num::ParseIntError, str::FromStr};
+use std::str::FromStr;
 
-#[derive(PartialEq, Eq, Hash, Debug)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
 struct Cell {
     x: i32,
     y: i32,
@@ -23,12 +25,15 @@ impl Cell {
     }
 }
 
-struct World {
+#[derive(Clone)]
+pub struct World {
     active_cells: HashSet<Cell>,
     borders_x: RangeInclusive<i32>,
     borders_y: RangeInclusive<i32>,
     borders_z: RangeInclusive<i32>,
     borders_w: RangeInclusive<i32>,
+    //When false the w dimension never grows, so the world is 3D
+    hyper: bool,
 }
 
 impl Iterator for World {
@@ -42,7 +47,7 @@ impl Iterator for World {
             .filter(|cell| {
                 let total = cell
                     .neighbours()
-                    .filter(|c| self.active_cells.contains(&c))
+                    .filter(|c| self.active_cells.contains(c))
                     .count();
                 (self.active_cells.contains(cell) && (2..=3).contains(&total))
                     || (!self.active_cells.contains(cell) && total == 3)
@@ -52,34 +57,44 @@ impl Iterator for World {
         self.borders_x = self.borders_x.start() - 1..=self.borders_x.end() + 1;
         self.borders_y = self.borders_y.start() - 1..=self.borders_y.end() + 1;
         self.borders_z = self.borders_z.start() - 1..=self.borders_z.end() + 1;
-        self.borders_w = self.borders_w.start() - 1..=self.borders_w.end() + 1;
+        if self.hyper {
+            self.borders_w = self.borders_w.start() - 1..=self.borders_w.end() + 1;
+        }
         self.active_cells = new_active_cells;
         Some(self.active_cells.len())
     }
 }
 
 impl FromStr for World {
-    type Err = ParseIntError;
+    type Err = AocError;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    fn from_str(s: &str) -> Result<Self> {
         let borders_y = -1..=s.lines().count() as i32;
-        let borders_x = -1..=s.lines().last().unwrap().len() as i32;
+        let borders_x = -1..=s.lines().map(|row| row.chars().count()).max().unwrap_or(0) as i32;
         let borders_z = -1..=1;
         let borders_w = -1..=1;
 
-        let active_cells: HashSet<Cell> = s
-            .lines()
-            .rev()
-            .enumerate()
-            .flat_map(|(i, row)| iter::repeat(i).zip(row.chars().enumerate()))
-            .filter(|(_i, (_j, c))| *c == '#')
-            .map(|(i, (j, _c))| Cell {
-                x: j as i32,
-                y: i as i32,
-                z: 0,
-                w: 0,
-            })
-            .collect();
+        let mut active_cells: HashSet<Cell> = HashSet::new();
+        for row in lines(s) {
+            for (j, c) in row.text.chars().enumerate() {
+                match c {
+                    '#' => {
+                        active_cells.insert(Cell {
+                            x: j as i32,
+                            //The first row is the top one
+                            y: (s.lines().count() - row.number) as i32,
+                            z: 0,
+                            w: 0,
+                        });
+                    }
+                    '.' => {}
+                    _ => {
+                        let message = format!("expected `.` or `#`, found `{}`", c);
+                        return Err(AocError::parse(row.number, j + 1, message));
+                    }
+                }
+            }
+        }
 
         Ok(World {
             active_cells,
@@ -87,25 +102,79 @@ impl FromStr for World {
             borders_y,
             borders_z,
             borders_w,
+            hyper: true,
         })
     }
 }
 
+impl World {
+    fn three_dimensional(mut self) -> Self {
+        self.borders_w = 0..=0;
+        self.hyper = false;
+        self
+    }
+}
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u8 = 17;
+    type Parsed<'a> = World;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<World> {
+        World::from_str(input)
+    }
+
+    fn part1(world: &World) -> Result<usize> {
+        Ok(world.clone().three_dimensional().nth(6 - 1).unwrap())
+    }
+
+    fn part2(world: &World) -> Result<usize> {
+        Ok(world.clone().nth(6 - 1).unwrap())
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::input::bundled;
+
+    #[test]
+    fn example1_part1() {
+        let input = &bundled("day17_example1.txt");
+        let world = World::from_str(input).unwrap().three_dimensional();
+        assert_eq!(world.into_iter().nth(6 - 1).unwrap(), 112);
+    }
+
+    #[test]
+    fn part1() {
+        let input = &bundled("day17.txt");
+        let world = World::from_str(input).unwrap().three_dimensional();
+        assert_eq!(world.into_iter().nth(6 - 1).unwrap(), 280);
+    }
 
     #[test]
     fn example1_part2() {
-        let input = include_str!("../inputs/day17_example1.txt");
+        let input = &bundled("day17_example1.txt");
         let world = World::from_str(input).unwrap();
         assert_eq!(world.into_iter().nth(6 - 1).unwrap(), 848);
     }
 
     #[test]
     fn part2() {
-        let input = include_str!("../inputs/day17.txt");
+        let input = &bundled("day17.txt");
         let world = World::from_str(input).unwrap();
         assert_eq!(world.into_iter().nth(6 - 1).unwrap(), 1696);
     }
+
+    #[test]
+    fn malformed_world() {
+        let error = World::from_str(".#.\n..x").err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 3: expected `.` or `#`, found `x`"
+        );
+    }
 }
@@ -1,33 +1,208 @@
 /*
-This day I took a rogue route instead of implementing a solution myself.
-I grabbed the crate `eval` and modified the priority of the operators sum and mul.
-
-```Rust
-#[test]
-fn part1_same_priority() {
-    let input = include_str!("../inputs/day18.txt");
-    let res : u64 = input.lines().map(|l| match eval(l).unwrap() {
-        Value::Null => 0,
-        Value::Bool(_) => 0,
-        Value::Number(n) => n.as_u64().unwrap(),
-        Value::String(_) => 0,
-        Value::Array(_) => 0,
-        Value::Object(_) => 0,
-    }).sum();
-    assert_eq!(res, 3885386961962);
-}
-
-#[test]
-fn part2_sum_higher_priority() {
-    let input = include_str!("../inputs/day18.txt");
-    let res : u64 = input.lines().map(|l| match eval(l).unwrap() {
-        Value::Null => 0,
-        Value::Bool(_) => 0,
-        Value::Number(n) => n.as_u64().unwrap(),
-        Value::String(_) => 0,
-        Value::Array(_) => 0,
-        Value::Object(_) => 0,
-    }).sum();
-    assert_eq!(res, 112899558798666);
-}
-*/
\ No newline at end of file
+My first approach was grabbing the crate `eval` and modifying the priority of
+the operators sum and mul, see hacks.md. This is a plain shunting-yard instead.
+*/
+use crate::error::{AocError, Result};
+use crate::parse::{lines, Line};
+use crate::Solution;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Token {
+    Number(u64),
+    Add,
+    Mul,
+    Open,
+    Close,
+}
+
+fn tokenize(line: Line) -> Result<Vec<Token>> {
+    //Also checks the expression is well formed, so evaluating it can't fail
+    let mut tokens = Vec::new();
+    let mut expect_operand = true;
+    let mut depth = 0;
+    let mut chars = line.text.chars().enumerate().peekable();
+    while let Some((column, c)) = chars.next() {
+        let token = match c {
+            ' ' => continue,
+            '+' => Token::Add,
+            '*' => Token::Mul,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '0'..='9' => {
+                let mut n = c.to_digit(10).unwrap() as u64;
+                while let Some(d) = chars.peek().and_then(|(_, d)| d.to_digit(10)) {
+                    n = n
+                        .checked_mul(10)
+                        .and_then(|n| n.checked_add(d as u64))
+                        .ok_or_else(|| {
+                            AocError::parse(line.number, column + 1, "number too big")
+                        })?;
+                    chars.next();
+                }
+                Token::Number(n)
+            }
+            _ => {
+                let message = format!("unexpected `{}`", c);
+                return Err(AocError::parse(line.number, column + 1, message));
+            }
+        };
+        let fits = match token {
+            Token::Number(_) | Token::Open => expect_operand,
+            Token::Close => !expect_operand && depth > 0,
+            Token::Add | Token::Mul => !expect_operand,
+        };
+        if !fits {
+            let message = format!("unexpected `{}`", c);
+            return Err(AocError::parse(line.number, column + 1, message));
+        }
+        match token {
+            Token::Open => depth += 1,
+            Token::Close => depth -= 1,
+            _ => expect_operand = !expect_operand,
+        }
+        tokens.push(token);
+    }
+    if expect_operand || depth > 0 {
+        return Err(line.missing(if expect_operand { "a number" } else { "`)`" }));
+    }
+    Ok(tokens)
+}
+
+fn apply(operands: &mut Vec<u64>, operator: Token) {
+    let (b, a) = (operands.pop().unwrap(), operands.pop().unwrap());
+    operands.push(match operator {
+        Token::Add => a + b,
+        Token::Mul => a * b,
+        _ => unreachable!(),
+    });
+}
+
+fn evaluate(tokens: &[Token], precedence: fn(Token) -> u8) -> u64 {
+    let mut operands: Vec<u64> = Vec::new();
+    let mut operators: Vec<Token> = Vec::new();
+    for &token in tokens {
+        match token {
+            Token::Number(n) => operands.push(n),
+            Token::Open => operators.push(token),
+            Token::Close => {
+                while let Some(operator) = operators.pop() {
+                    if operator == Token::Open {
+                        break;
+                    }
+                    apply(&mut operands, operator);
+                }
+            }
+            Token::Add | Token::Mul => {
+                //Left associative, so equal priority operators are applied first
+                while let Some(&top) = operators.last() {
+                    if top == Token::Open || precedence(top) < precedence(token) {
+                        break;
+                    }
+                    apply(&mut operands, top);
+                    operators.pop();
+                }
+                operators.push(token);
+            }
+        }
+    }
+    while let Some(operator) = operators.pop() {
+        apply(&mut operands, operator);
+    }
+    operands.pop().unwrap()
+}
+
+fn same_priority(_operator: Token) -> u8 {
+    0
+}
+
+fn sum_higher_priority(operator: Token) -> u8 {
+    match operator {
+        Token::Add => 1,
+        _ => 0,
+    }
+}
+
+fn sum_of_results(expressions: &[Vec<Token>], precedence: fn(Token) -> u8) -> u64 {
+    expressions.iter().map(|e| evaluate(e, precedence)).sum()
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    const DAY: u8 = 18;
+    type Parsed<'a> = Vec<Vec<Token>>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Vec<Vec<Token>>> {
+        lines(input).map(tokenize).collect()
+    }
+
+    fn part1(expressions: &Vec<Vec<Token>>) -> Result<u64> {
+        Ok(sum_of_results(expressions, same_priority))
+    }
+
+    fn part2(expressions: &Vec<Vec<Token>>) -> Result<u64> {
+        Ok(sum_of_results(expressions, sum_higher_priority))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::input::bundled;
+
+    #[test]
+    fn example_part1() {
+        let tokens = tokenize(Line::new(1, "1 + 2 * 3 + 4 * 5 + 6")).unwrap();
+        assert_eq!(evaluate(&tokens, same_priority), 71);
+        let tokens = tokenize(Line::new(
+            1,
+            "((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2",
+        ))
+        .unwrap();
+        assert_eq!(evaluate(&tokens, same_priority), 13632);
+    }
+
+    #[test]
+    fn part1() {
+        let input = &bundled("day18.txt");
+        assert_eq!(
+            Day18::part1(&Day18::parse(input).unwrap()).unwrap(),
+            3885386961962
+        );
+    }
+
+    #[test]
+    fn example_part2() {
+        let tokens = tokenize(Line::new(1, "1 + 2 * 3 + 4 * 5 + 6")).unwrap();
+        assert_eq!(evaluate(&tokens, sum_higher_priority), 231);
+        let tokens = tokenize(Line::new(
+            1,
+            "((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2",
+        ))
+        .unwrap();
+        assert_eq!(evaluate(&tokens, sum_higher_priority), 23340);
+    }
+
+    #[test]
+    fn part2() {
+        let input = &bundled("day18.txt");
+        assert_eq!(
+            Day18::part2(&Day18::parse(input).unwrap()).unwrap(),
+            112899558798666
+        );
+    }
+
+    #[test]
+    fn malformed_expressions() {
+        let error = Day18::parse("1 + 2\n3 - 4").unwrap_err();
+        assert_eq!(error.to_string(), "line 2, column 3: unexpected `-`");
+        let error = Day18::parse("(1 + 2))").unwrap_err();
+        assert_eq!(error.to_string(), "line 1, column 8: unexpected `)`");
+        let error = Day18::parse("2 * (3 +").unwrap_err();
+        assert_eq!(error.to_string(), "line 1, column 9: expected a number");
+        let error = Day18::parse("2 * (3 + 4").unwrap_err();
+        assert_eq!(error.to_string(), "line 1, column 11: expected `)`");
+    }
+}
@@ -1,33 +1,57 @@
+use crate::error::{AocError, Result};
+use crate::input::normalize;
+use crate::parse::{lines, Line};
+use crate::Solution;
 use itertools::Itertools;
+use serde::Serialize;
+use std::fmt::{self, Display};
 
 #[derive(Debug, Clone)]
-struct Policy {
-    min: usize,
-    max: usize,
-    letter: char,
+pub struct Policy {
+    pub min: usize,
+    pub max: usize,
+    pub letter: char,
+}
+
+impl Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{} {}", self.min, self.max, self.letter)
+    }
 }
 
 #[derive(Debug, Clone)]
-struct Database<'a> {
-    policy: Policy,
-    password: &'a str,
+pub struct Database<'a> {
+    pub line: usize,
+    pub policy: Policy,
+    pub password: &'a str,
+    //Whether the byte versions give the right answer
+    ascii: bool,
 }
 
 impl<'a> Database<'a> {
-    fn from_str(input: &'a str) -> Self {
-        input
-            .split([':', '-', ' '].as_ref())
-            .filter(|s| !s.is_empty())
-            .collect_tuple()
-            .map(|(min, max, letter, password)| Database {
-                policy: Policy {
-                    min: min.parse().unwrap(),
-                    max: max.parse().unwrap(),
-                    letter: letter.chars().next().unwrap(),
-                },
-                password: password,
-            })
-            .unwrap()
+    fn new(line: usize, policy: Policy, password: &'a str) -> Self {
+        let ascii = policy.letter.is_ascii() && password.is_ascii();
+        Database {
+            line,
+            policy,
+            password,
+            ascii,
+        }
+    }
+
+    fn from_str(line: Line<'a>) -> Result<Self> {
+        let [min, max, letter, password] = line.fields("{min}-{max} {letter}: {password}")?;
+        let mut letters = letter.chars();
+        let letter = match (letters.next(), letters.next()) {
+            (Some(c), None) => c,
+            _ => return Err(line.error(letter, format!("expected one letter, found `{}`", letter))),
+        };
+        let policy = Policy {
+            min: line.parse(min)?,
+            max: line.parse(max)?,
+            letter,
+        };
+        Ok(Database::new(line.number, policy, password))
     }
 
     fn is_valid(&self) -> bool {
@@ -36,41 +60,639 @@ impl<'a> Database<'a> {
     }
 
     fn is_valid_part2(&self) -> bool {
-        (self.password.chars().nth(self.policy.min - 1).unwrap() == self.policy.letter)
-            ^ (self.password.chars().nth(self.policy.max - 1).unwrap() == self.policy.letter)
+        let at = |position: usize| {
+            position
+                .checked_sub(1)
+                .and_then(|i| self.password.chars().nth(i))
+                == Some(self.policy.letter)
+        };
+        at(self.policy.min) ^ at(self.policy.max)
+    }
+
+    //Byte versions from hacks.md, faster but only right for ASCII passwords
+    fn is_valid_bytes(&self) -> bool {
+        let letter = self.policy.letter as u8;
+        #[cfg(feature = "fast-ascii")]
+        let frequency = bytecount::count(self.password.as_bytes(), letter);
+        #[cfg(not(feature = "fast-ascii"))]
+        let frequency = self.password.bytes().filter(|&b| b == letter).count();
+        (self.policy.min..=self.policy.max).contains(&frequency)
+    }
+
+    fn is_valid_part2_bytes(&self) -> bool {
+        let letter = self.policy.letter as u8;
+        let at = |position: usize| {
+            position
+                .checked_sub(1)
+                .and_then(|i| self.password.as_bytes().get(i))
+                == Some(&letter)
+        };
+        at(self.policy.min) ^ at(self.policy.max)
+    }
+}
+
+/// A rule the passwords in the database must follow.
+pub trait PasswordPolicy {
+    fn accepts(&self, entry: &Database) -> bool;
+
+    /// Why the entry is rejected, or `None` if it's accepted.
+    fn reject(&self, entry: &Database) -> Option<String> {
+        (!self.accepts(entry)).then(|| "rejected by the policy".to_string())
+    }
+}
+
+impl<P: PasswordPolicy + ?Sized> PasswordPolicy for Box<P> {
+    fn accepts(&self, entry: &Database) -> bool {
+        (**self).accepts(entry)
+    }
+
+    fn reject(&self, entry: &Database) -> Option<String> {
+        (**self).reject(entry)
+    }
+}
+
+/// The sled rental rule: the letter appears between min and max times.
+pub struct LetterCount;
+
+impl PasswordPolicy for LetterCount {
+    fn accepts(&self, entry: &Database) -> bool {
+        if cfg!(feature = "fast-ascii") && entry.ascii {
+            return entry.is_valid_bytes();
+        }
+        entry.is_valid()
+    }
+
+    fn reject(&self, entry: &Database) -> Option<String> {
+        let policy = &entry.policy;
+        let frequency = entry.password.matches(policy.letter).count();
+        (!self.accepts(entry)).then(|| {
+            format!(
+                "`{}` appears {} times, not {} to {}",
+                policy.letter, frequency, policy.min, policy.max
+            )
+        })
+    }
+}
+
+/// The toboggan rule: the letter is at exactly one of both positions, counting from 1.
+pub struct LetterPositions;
+
+impl PasswordPolicy for LetterPositions {
+    fn accepts(&self, entry: &Database) -> bool {
+        if cfg!(feature = "fast-ascii") && entry.ascii {
+            return entry.is_valid_part2_bytes();
+        }
+        entry.is_valid_part2()
+    }
+
+    fn reject(&self, entry: &Database) -> Option<String> {
+        if self.accepts(entry) {
+            return None;
+        }
+        let Policy { min, max, letter } = entry.policy;
+        let length = entry.password.chars().count();
+        Some(
+            match [min, max].iter().copied().find(|&p| p == 0 || p > length) {
+                Some(0) => "positions start at 1".to_string(),
+                Some(past) => format!(
+                    "position {} is past the end of the {} letter password",
+                    past, length
+                ),
+                None if entry.password.chars().nth(min - 1) == Some(letter) => {
+                    format!("`{}` is at both positions {} and {}", letter, min, max)
+                }
+                None => format!("`{}` is at neither position {} nor {}", letter, min, max),
+            },
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharClass {
+    Lower,
+    Upper,
+    Digit,
+    Letter,
+    Symbol,
+}
+
+impl CharClass {
+    fn contains(self, c: char) -> bool {
+        match self {
+            CharClass::Lower => c.is_lowercase(),
+            CharClass::Upper => c.is_uppercase(),
+            CharClass::Digit => c.is_numeric(),
+            CharClass::Letter => c.is_alphabetic(),
+            CharClass::Symbol => !c.is_alphanumeric() && !c.is_whitespace(),
+        }
     }
 }
 
-fn get_input(raw_input: &str) -> impl Iterator<Item = Database> {
-    let databases = raw_input.lines().map(Database::from_str);
-    databases
+/// At least `min` characters of a class.
+pub struct ClassMinimum {
+    pub class: CharClass,
+    pub min: usize,
+}
+
+impl PasswordPolicy for ClassMinimum {
+    fn accepts(&self, entry: &Database) -> bool {
+        let count = entry.password.chars().filter(|&c| self.class.contains(c));
+        count.count() >= self.min
+    }
+
+    fn reject(&self, entry: &Database) -> Option<String> {
+        let count = entry.password.chars().filter(|&c| self.class.contains(c));
+        let count = count.count();
+        (count < self.min).then(|| {
+            let class = format!("{:?}", self.class).to_lowercase();
+            format!("{} {} characters, expected {}", count, class, self.min)
+        })
+    }
+}
+
+/// Doesn't contain the substring.
+pub struct Forbidden(pub String);
+
+impl PasswordPolicy for Forbidden {
+    fn accepts(&self, entry: &Database) -> bool {
+        !entry.password.contains(self.0.as_str())
+    }
+
+    fn reject(&self, entry: &Database) -> Option<String> {
+        (!self.accepts(entry)).then(|| format!("contains `{}`", self.0))
+    }
 }
 
-pub fn count_valids_first(input: &str) -> usize {
-    let databases = get_input(input);
-    databases.filter(|d| d.is_valid()).count()
+/// No character repeated more than this many times in a row.
+pub struct MaxRun(pub usize);
+
+impl PasswordPolicy for MaxRun {
+    fn accepts(&self, entry: &Database) -> bool {
+        let mut runs = entry.password.chars().dedup_with_count();
+        runs.all(|(run, _c)| run <= self.0)
+    }
+
+    fn reject(&self, entry: &Database) -> Option<String> {
+        let mut runs = entry.password.chars().dedup_with_count();
+        let (run, c) = runs.find(|&(run, _c)| run > self.0)?;
+        Some(format!(
+            "`{}` repeated {} times in a row, expected at most {}",
+            c, run, self.0
+        ))
+    }
 }
 
-pub fn count_valids_second(input: &str) -> usize {
-    let databases = get_input(input);
-    databases.filter(|d| d.is_valid_part2()).count()
+pub struct All(pub Vec<Box<dyn PasswordPolicy>>);
+
+impl PasswordPolicy for All {
+    fn accepts(&self, entry: &Database) -> bool {
+        self.0.iter().all(|policy| policy.accepts(entry))
+    }
+
+    fn reject(&self, entry: &Database) -> Option<String> {
+        self.0.iter().find_map(|policy| policy.reject(entry))
+    }
+}
+
+pub struct Any(pub Vec<Box<dyn PasswordPolicy>>);
+
+impl PasswordPolicy for Any {
+    fn accepts(&self, entry: &Database) -> bool {
+        self.0.iter().any(|policy| policy.accepts(entry))
+    }
+
+    fn reject(&self, entry: &Database) -> Option<String> {
+        let reasons: Option<Vec<String>> = self.0.iter().map(|p| p.reject(entry)).collect();
+        reasons.map(|reasons| reasons.join(", and "))
+    }
+}
+
+pub struct Not(pub Box<dyn PasswordPolicy>);
+
+impl PasswordPolicy for Not {
+    fn accepts(&self, entry: &Database) -> bool {
+        !self.0.accepts(entry)
+    }
+
+    fn reject(&self, entry: &Database) -> Option<String> {
+        self.0
+            .accepts(entry)
+            .then(|| "accepted by the negated policy".to_string())
+    }
+}
+
+/// Builds a policy from a spec such as `all(count, not(forbid("abc")), min(digit, 1))`.
+///
+/// The rules are `count` and `positions` (parts 1 and 2), `min(<class>, <n>)` with
+/// lower, upper, digit, letter or symbol, `forbid("<text>")`, `max-run(<n>)`,
+/// and `all(...)`, `any(...)` and `not(<rule>)` to combine them.
+pub fn parse_policy(spec: &str) -> Result<Box<dyn PasswordPolicy>> {
+    let mut parser = SpecParser { spec, position: 0 };
+    let policy = parser.policy()?;
+    parser.skip_spaces();
+    if parser.position < spec.len() {
+        return Err(parser.error("expected the end of the policy"));
+    }
+    Ok(policy)
+}
+
+struct SpecParser<'a> {
+    spec: &'a str,
+    position: usize,
+}
+
+impl<'a> SpecParser<'a> {
+    fn error(&self, message: impl Into<String>) -> AocError {
+        let column = self.spec[..self.position].chars().count() + 1;
+        AocError::parse(1, column, message)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.spec[self.position..]
+    }
+
+    fn skip_spaces(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn take(&mut self, token: char) -> bool {
+        self.skip_spaces();
+        let found = self.rest().starts_with(token);
+        if found {
+            self.position += token.len_utf8();
+        }
+        found
+    }
+
+    fn expect(&mut self, token: char) -> Result<()> {
+        if self.take(token) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{}`", token)))
+        }
+    }
+
+    fn word(&mut self) -> &'a str {
+        //Names like max-run, or numbers
+        self.skip_spaces();
+        let rest = self.rest();
+        let end = rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+            .unwrap_or(rest.len());
+        self.position += end;
+        &rest[..end]
+    }
+
+    fn number(&mut self) -> Result<usize> {
+        let start = self.position;
+        let word = self.word();
+        word.parse().map_err(|_| {
+            self.position = start;
+            self.error(format!("expected a number, found `{}`", word))
+        })
+    }
+
+    fn text(&mut self) -> Result<String> {
+        self.expect('"')?;
+        let end = self
+            .rest()
+            .find('"')
+            .ok_or_else(|| self.error("unclosed `\"`"))?;
+        let text = self.rest()[..end].to_string();
+        self.position += end + 1;
+        Ok(text)
+    }
+
+    fn policies(&mut self) -> Result<Vec<Box<dyn PasswordPolicy>>> {
+        let mut policies = vec![self.policy()?];
+        while self.take(',') {
+            policies.push(self.policy()?);
+        }
+        Ok(policies)
+    }
+
+    fn policy(&mut self) -> Result<Box<dyn PasswordPolicy>> {
+        self.skip_spaces();
+        let start = self.position;
+        let name = self.word();
+        let policy: Box<dyn PasswordPolicy> = match name {
+            "count" => return Ok(Box::new(LetterCount)),
+            "positions" => return Ok(Box::new(LetterPositions)),
+            "min" | "forbid" | "max-run" | "all" | "any" | "not" => {
+                self.expect('(')?;
+                match name {
+                    "min" => {
+                        self.skip_spaces();
+                        let class_start = self.position;
+                        let class = match self.word() {
+                            "lower" => CharClass::Lower,
+                            "upper" => CharClass::Upper,
+                            "digit" => CharClass::Digit,
+                            "letter" => CharClass::Letter,
+                            "symbol" => CharClass::Symbol,
+                            other => {
+                                let message = format!("unknown character class `{}`", other);
+                                self.position = class_start;
+                                return Err(self.error(message));
+                            }
+                        };
+                        self.expect(',')?;
+                        Box::new(ClassMinimum {
+                            class,
+                            min: self.number()?,
+                        })
+                    }
+                    "forbid" => Box::new(Forbidden(self.text()?)),
+                    "max-run" => Box::new(MaxRun(self.number()?)),
+                    "all" => Box::new(All(self.policies()?)),
+                    "any" => Box::new(Any(self.policies()?)),
+                    _ => Box::new(Not(self.policy()?)),
+                }
+            }
+            other => {
+                let message = format!("unknown rule `{}`", other);
+                self.position = start;
+                return Err(self.error(message));
+            }
+        };
+        self.expect(')')?;
+        Ok(policy)
+    }
+}
+
+fn get_input(raw_input: &str) -> Result<Vec<Database<'_>>> {
+    lines(raw_input).map(Database::from_str).collect()
+}
+
+fn count_accepted<P: PasswordPolicy + ?Sized>(databases: &[Database], policy: &P) -> usize {
+    databases.iter().filter(|d| policy.accepts(d)).count()
+}
+
+/// How many lines of a raw database follow the policy.
+pub fn count_valid<P: PasswordPolicy + ?Sized>(input: &str, policy: &P) -> Result<usize> {
+    let input = normalize(input);
+    Ok(count_accepted(&get_input(&input)?, policy))
+}
+
+/// How one line of the database fares against a policy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Verdict {
+    pub line: usize,
+    pub policy: String,
+    pub password: String,
+    pub valid: bool,
+    pub reason: Option<String>,
+}
+
+/// Checks every line of a raw database, instead of just counting the valid ones.
+pub fn validate<P: PasswordPolicy + ?Sized>(input: &str, policy: &P) -> Result<Vec<Verdict>> {
+    let input = normalize(input);
+    let verdicts = get_input(&input)?
+        .iter()
+        .map(|entry| {
+            let reason = policy.reject(entry);
+            Verdict {
+                line: entry.line,
+                policy: entry.policy.to_string(),
+                password: entry.password.to_string(),
+                valid: reason.is_none(),
+                reason,
+            }
+        })
+        .collect();
+    Ok(verdicts)
+}
+
+pub fn verdicts_to_table(verdicts: &[Verdict]) -> String {
+    let width = |column: fn(&Verdict) -> usize, header: &str| {
+        verdicts
+            .iter()
+            .map(column)
+            .chain(Some(header.len()))
+            .max()
+            .unwrap_or(0)
+    };
+    let policy = width(|v| v.policy.chars().count(), "Policy");
+    let password = width(|v| v.password.chars().count(), "Password");
+    let mut table = format!(
+        "Line  {:<policy$}  {:<password$}  Verdict  Reason\n",
+        "Policy",
+        "Password",
+        policy = policy,
+        password = password
+    );
+    for verdict in verdicts {
+        let row = format!(
+            "{:>4}  {:<policy$}  {:<password$}  {:<7}  {}",
+            verdict.line,
+            verdict.policy,
+            verdict.password,
+            if verdict.valid { "valid" } else { "invalid" },
+            verdict.reason.as_deref().unwrap_or(""),
+            policy = policy,
+            password = password
+        );
+        table.push_str(row.trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+pub fn verdicts_to_json(verdicts: &[Verdict]) -> String {
+    serde_json::to_string_pretty(verdicts).expect("verdicts are plain data")
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u8 = 2;
+    type Parsed<'a> = Vec<Database<'a>>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Vec<Database<'_>>> {
+        get_input(input)
+    }
+
+    fn part1(databases: &Vec<Database>) -> Result<usize> {
+        Ok(count_accepted(databases, &LetterCount))
+    }
+
+    fn part2(databases: &Vec<Database>) -> Result<usize> {
+        Ok(count_accepted(databases, &LetterPositions))
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::input::bundled;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use proptest::sample::select;
+
+    //ASCII passwords, sometimes shorter than the highest position
+    fn policy_and_password() -> impl Strategy<Value = (Policy, String)> {
+        let letters = || select(vec!['a', 'b', 'c']);
+        (0..8usize, 0..8usize, letters()).prop_flat_map(move |(min, extra, letter)| {
+            let max = min + extra;
+            let password = vec(letters(), 0..max + 12);
+            let password = password.prop_map(|letters| letters.into_iter().collect::<String>());
+            (Just(Policy { min, max, letter }), password)
+        })
+    }
 
     #[test]
     pub fn part1() {
-        let raw = include_str!("../inputs/day2_p2.txt");
-        let are_valid = count_valids_first(&raw);
+        let raw = &bundled("day2.txt");
+        let are_valid = count_valid(raw, &LetterCount).unwrap();
         assert_eq!(are_valid, 465);
     }
 
     #[test]
     pub fn part2() {
-        let raw = include_str!("../inputs/day2_p2.txt");
-        let are_valid = count_valids_second(&raw);
+        let raw = &bundled("day2.txt");
+        let are_valid = count_valid(raw, &LetterPositions).unwrap();
         assert_eq!(are_valid, 294);
     }
+
+    proptest! {
+        #[test]
+        fn byte_versions_agree((policy, password) in policy_and_password()) {
+            let database = Database::new(1, policy, &password);
+            prop_assert_eq!(database.is_valid_bytes(), database.is_valid());
+            prop_assert_eq!(database.is_valid_part2_bytes(), database.is_valid_part2());
+        }
+    }
+
+    #[test]
+    pub fn non_ascii() {
+        //The byte versions would count the bytes of é, or look inside them
+        let raw = "2-3 é: éaéxé\n2-3 a: éaa\n2-2 a: éa";
+        assert!(!get_input(raw).unwrap()[0].ascii);
+        assert_eq!(count_valid(raw, &LetterCount).unwrap(), 2);
+        assert_eq!(count_valid(raw, &LetterPositions).unwrap(), 1);
+        let databases = get_input("1-3 a: abcde").unwrap();
+        assert!(databases[0].ascii);
+    }
+
+    #[test]
+    pub fn policies() {
+        let raw = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n1-2 x: Ab1#";
+        let count = |spec: &str| count_valid(raw, &parse_policy(spec).unwrap()).unwrap();
+        assert_eq!(count("count"), 2);
+        assert_eq!(count("positions"), 1);
+        assert_eq!(count("all(count, positions)"), 1);
+        assert_eq!(count("any(count, positions)"), 2);
+        assert_eq!(count("not(count)"), 2);
+        assert_eq!(count("max-run(3)"), 3);
+        assert_eq!(count(r#"forbid("cd")"#), 2);
+        assert_eq!(count("min(upper, 1)"), 1);
+        assert_eq!(
+            count(" all ( min(digit,1) , min(symbol, 1), min(lower, 1) ) "),
+            1
+        );
+        assert_eq!(count("min(letter, 5)"), 3);
+
+        //Past the end of the password, or at position 0
+        let raw = "3-9 a: ab\r\n0-1 a: a\r\n";
+        assert_eq!(count_valid(raw, &LetterPositions).unwrap(), 1);
+
+        let raw = &bundled("day2.txt");
+        let databases = get_input(raw).unwrap();
+        let both = All(vec![Box::new(LetterCount), Box::new(LetterPositions)]);
+        let both = count_accepted(&databases, &both);
+        let neither = Not(parse_policy("any(count, positions)").unwrap());
+        let neither = count_accepted(&databases, &neither);
+        assert_eq!(neither, databases.len() - (465 + 294 - both));
+    }
+
+    #[test]
+    pub fn report() {
+        let input = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n3-9 c: cc\n0-1 a: b";
+        let verdicts = validate(input, &LetterPositions).unwrap();
+        let lines: Vec<usize> = verdicts.iter().map(|v| v.line).collect();
+        assert_eq!(lines, vec![1, 2, 3, 4, 5]);
+        assert_eq!(verdicts[0].reason, None);
+        let reasons: Vec<&str> = verdicts[1..]
+            .iter()
+            .map(|v| v.reason.as_deref().unwrap())
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                "`b` is at neither position 1 nor 3",
+                "`c` is at both positions 2 and 9",
+                "position 3 is past the end of the 2 letter password",
+                "positions start at 1",
+            ]
+        );
+        let verdicts = validate(input, &parse_policy("all(count, max-run(4))").unwrap()).unwrap();
+        assert_eq!(
+            verdicts[1].reason.as_deref(),
+            Some("`b` appears 0 times, not 1 to 3")
+        );
+        assert_eq!(
+            verdicts[2].reason.as_deref(),
+            Some("`c` repeated 9 times in a row, expected at most 4")
+        );
+        assert_eq!(
+            verdicts_to_table(&verdicts[..2]),
+            "Line  Policy  Password  Verdict  Reason\n   \
+               1  1-3 a   abcde     valid\n   \
+               2  1-3 b   cdefg     invalid  `b` appears 0 times, not 1 to 3\n"
+        );
+        let json: serde_json::Value = serde_json::from_str(&verdicts_to_json(&verdicts)).unwrap();
+        assert_eq!(json[0]["policy"], "1-3 a");
+        assert_eq!(json[0]["valid"], true);
+        assert_eq!(json[0]["reason"], serde_json::Value::Null);
+        assert!(validate("1-3 a", &LetterCount).is_err());
+
+        let verdicts = validate(input, &parse_policy("not(positions)").unwrap()).unwrap();
+        assert_eq!(
+            verdicts[0].reason.as_deref(),
+            Some("accepted by the negated policy")
+        );
+        assert_eq!(verdicts[1].reason, None);
+    }
+
+    #[test]
+    pub fn malformed_policies() {
+        let error = |spec| parse_policy(spec).err().unwrap().to_string();
+        assert_eq!(error("cout"), "line 1, column 1: unknown rule `cout`");
+        assert_eq!(
+            error("all(count positions)"),
+            "line 1, column 11: expected `)`"
+        );
+        assert_eq!(
+            error("min(vowel, 2)"),
+            "line 1, column 5: unknown character class `vowel`"
+        );
+        assert_eq!(
+            error("max-run(x)"),
+            "line 1, column 9: expected a number, found `x`"
+        );
+        assert_eq!(error(r#"forbid("ab)"#), "line 1, column 9: unclosed `\"`");
+        assert_eq!(
+            error("count)"),
+            "line 1, column 6: expected the end of the policy"
+        );
+    }
+
+    #[test]
+    pub fn malformed_lines() {
+        let error = get_input("1-3 a: abcde\n1-x b: cdefg").unwrap_err();
+        assert_eq!(error.to_string(), "line 2, column 3: invalid number `x`");
+        let error = get_input("1-3 ab: abcde").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 5: expected one letter, found `ab`"
+        );
+        let error = get_input("1-3 a").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 5: expected `<min>-<max> <letter>: <password>`"
+        );
+    }
 }
@@ -1,49 +1,199 @@
-use std::ops::Mul;
-struct Slope {
-    right: usize,
-    down: usize,
+use crate::error::Result;
+use crate::grid::{Grid, Point, Wrap};
+use crate::Solution;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+/// How far the toboggan moves at each step. Negative `right` goes left, and
+/// negative `down` leaves the map right away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slope {
+    pub right: i64,
+    pub down: i64,
 }
 
-impl Mul<usize> for &Slope {
-    type Output = Slope;
-    fn mul(self, rhs: usize) -> Slope {
-        Slope {
-            right: self.right * rhs,
-            down: self.down * rhs,
+impl Slope {
+    /// Moving `right` / `down` cells to the right per row, stopping only where
+    /// the line crosses a cell: 3 / 2 steps 3 right and 2 down, and so does 6 / 4.
+    pub fn rational(right: i64, down: i64) -> Option<Self> {
+        if down == 0 {
+            return None;
         }
+        let divisor = gcd(right, down) * down.signum();
+        Some(Slope {
+            right: right / divisor,
+            down: down / divisor,
+        })
     }
 }
 
-struct Map {
-    grid: Vec<char>,
-    height: usize,
-    width: usize,
+fn gcd(a: i64, b: i64) -> i64 {
+    match b {
+        0 => a.abs(),
+        _ => gcd(b, a % b),
+    }
+}
+
+/// A cell the toboggan goes through. The position doesn't wrap: the map repeats
+/// to the right and to the left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Visit {
+    pub position: Point,
+    pub tree: bool,
+}
+
+/// Which slopes come first when ranking them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    FewestTrees,
+    MostTrees,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ranked {
+    pub slope: Slope,
+    pub trees: usize,
+}
+
+#[derive(Debug)]
+pub struct Map {
+    //Whether there's a tree, the pattern repeats to the right
+    trees: Grid<bool>,
 }
 
 impl Map {
-    fn new(raw_map: &str) -> Self {
-        Map {
-            grid: raw_map
-                .chars()
-                .filter(|&cell| cell == '.' || cell == '#')
-                .collect(),
-            height: raw_map.lines().count(),
-            width: raw_map.lines().next().unwrap().len(),
+    fn new(raw_map: &str) -> Result<Self> {
+        let trees = Grid::parse(raw_map, &[('.', false), ('#', true)])?;
+        Ok(Map {
+            trees: trees.with_wrap(Wrap::Horizontal),
+        })
+    }
+
+    fn visits(&self, slope: Slope) -> impl Iterator<Item = Visit> + '_ {
+        //Going sideways only comes back to the start after a while
+        let laps = match slope.down {
+            0 => (self.trees.width() as i64 / gcd(self.trees.width() as i64, slope.right)) as usize,
+            _ => usize::MAX,
+        };
+        let step = Point::new(slope.right, slope.down);
+        let start = (Point::ORIGIN, &self.trees[Point::ORIGIN]);
+        std::iter::once(start)
+            .chain(self.trees.ray(Point::ORIGIN, step))
+            .take(laps)
+            .map(|(position, &tree)| Visit { position, tree })
+    }
+
+    /// Every cell from the top left corner until going past the bottom.
+    pub fn path(&self, slope: Slope) -> Vec<Visit> {
+        self.visits(slope).collect()
+    }
+
+    pub fn count_trees_traversed(&self, slope: Slope) -> usize {
+        self.visits(slope).filter(|visit| visit.tree).count()
+    }
+
+    /// The map with the path drawn as in the puzzle, `O` for open cells and
+    /// `X` for trees, repeated enough times to fit the whole path.
+    pub fn render(&self, slope: Slope) -> String {
+        let path = self.path(slope);
+        let width = self.trees.width() as i64;
+        let tile = |x: i64| x.div_euclid(width);
+        let left = path.iter().map(|v| tile(v.position.x)).min().unwrap_or(0);
+        let right = path.iter().map(|v| tile(v.position.x)).max().unwrap_or(0);
+        let visited: HashSet<Point> = path.iter().map(|visit| visit.position).collect();
+        let mut render = String::new();
+        for y in 0..self.trees.height() as i64 {
+            for x in left * width..(right + 1) * width {
+                let point = Point::new(x, y);
+                render.push(match (visited.contains(&point), self.trees[point]) {
+                    (true, true) => 'X',
+                    (true, false) => 'O',
+                    (false, true) => '#',
+                    (false, false) => '.',
+                });
+            }
+            render.push('\n');
         }
+        render
     }
 
-    fn get_value(&self, slope: &Slope) -> char {
-        let position = (slope.down * self.width) + (slope.right % self.width);
-        self.grid[position]
+    /// Every slope with `right` and `down` in the ranges, best first. Ties keep
+    /// the order of the ranges, by `down` and then by `right`.
+    pub fn rank_slopes(
+        &self,
+        right: RangeInclusive<i64>,
+        down: RangeInclusive<i64>,
+        goal: Goal,
+    ) -> Vec<Ranked> {
+        self.rank_slopes_walking(right, down, goal, |_down| {})
     }
 
-    fn count_trees_traversed(&self, slope: Slope) -> usize {
-        let steps = (self.height / slope.down) - 1;
-        (0..steps)
-            .map(|step| &slope * (step + 1))
-            .map(|slope| self.get_value(&slope))
-            .filter(|cell| *cell == '#')
-            .count()
+    //Calls `walked` each time it goes down the rows, with how many rows per step
+    fn rank_slopes_walking(
+        &self,
+        right: RangeInclusive<i64>,
+        down: RangeInclusive<i64>,
+        goal: Goal,
+        mut walked: impl FnMut(i64),
+    ) -> Vec<Ranked> {
+        let rights: Vec<i64> = right.collect();
+        let mut ranked = Vec::new();
+        for down in down {
+            let trees = match down {
+                down if down > 0 => {
+                    walked(down);
+                    self.count_trees_down(&rights, down)
+                }
+                //Those never leave the first row
+                _ => rights
+                    .iter()
+                    .map(|&right| self.count_trees_traversed(Slope { right, down }))
+                    .collect(),
+            };
+            let slopes = rights.iter().map(|&right| Slope { right, down });
+            ranked.extend(
+                slopes
+                    .zip(trees)
+                    .map(|(slope, trees)| Ranked { slope, trees }),
+            );
+        }
+        match goal {
+            Goal::FewestTrees => ranked.sort_by_key(|r| r.trees),
+            Goal::MostTrees => ranked.sort_by_key(|r| Reverse(r.trees)),
+        }
+        ranked
+    }
+
+    //Trees hit going `down` rows per step with each of the rights, in a single
+    //pass over the rows
+    fn count_trees_down(&self, rights: &[i64], down: i64) -> Vec<usize> {
+        let width = self.trees.width() as i64;
+        let mut counts = vec![0; rights.len()];
+        let mut columns = vec![0; rights.len()];
+        for y in (0..self.trees.height() as i64).step_by(down as usize) {
+            for (i, right) in rights.iter().enumerate() {
+                counts[i] += self.trees[Point::new(columns[i], y)] as usize;
+                columns[i] = (columns[i] + right).rem_euclid(width);
+            }
+        }
+        counts
+    }
+
+    pub fn packed(&self) -> PackedMap {
+        let (width, height) = (self.trees.width(), self.trees.height());
+        let words = width.div_ceil(64);
+        let mut bits = vec![0u64; words * height];
+        for (point, &tree) in self.trees.iter() {
+            let (x, y) = (point.x as usize, point.y as usize);
+            bits[y * words + x / 64] |= (tree as u64) << (x % 64);
+        }
+        PackedMap {
+            width,
+            height,
+            words,
+            bits,
+        }
     }
 
     fn count_trees_together(&self, slopes: Vec<Slope>) -> usize {
@@ -54,22 +204,118 @@ impl Map {
     }
 }
 
+/// The map with a bit per cell, each row in `u64` words, for very big maps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackedMap {
+    width: usize,
+    height: usize,
+    //Words per row
+    words: usize,
+    bits: Vec<u64>,
+}
+
+impl PackedMap {
+    fn tree(&self, x: usize, y: usize) -> bool {
+        self.bits[y * self.words + x / 64] >> (x % 64) & 1 == 1
+    }
+
+    pub fn count_trees(&self, slope: Slope) -> usize {
+        self.count_trees_batch(&[slope])[0]
+    }
+
+    /// Trees hit by each slope, going through the rows once for all of them.
+    pub fn count_trees_batch(&self, slopes: &[Slope]) -> Vec<usize> {
+        let width = self.width as i64;
+        let steps: Vec<usize> = slopes
+            .iter()
+            .map(|slope| slope.right.rem_euclid(width) as usize)
+            .collect();
+        //Slopes going down the same number of rows, with the next row they go through
+        let mut groups: Vec<(usize, usize, Vec<usize>)> = Vec::new();
+        for (i, slope) in slopes.iter().enumerate().filter(|(_i, s)| s.down > 0) {
+            let down = slope.down as usize;
+            match groups.iter_mut().find(|(d, _row, _slopes)| *d == down) {
+                Some((_d, _row, members)) => members.push(i),
+                None => groups.push((down, 0, vec![i])),
+            }
+        }
+        let mut counts = vec![0; slopes.len()];
+        let mut columns = vec![0; slopes.len()];
+        for y in 0..self.height {
+            for (down, row, members) in &mut groups {
+                if *row != y {
+                    continue;
+                }
+                *row += *down;
+                for &i in members.iter() {
+                    counts[i] += self.tree(columns[i], y) as usize;
+                    columns[i] += steps[i];
+                    if columns[i] >= self.width {
+                        columns[i] -= self.width;
+                    }
+                }
+            }
+        }
+        //Those that never go down stay on the first row
+        for (i, slope) in slopes.iter().enumerate() {
+            counts[i] = match slope.down {
+                0 => {
+                    let laps = self.width / gcd(width, slope.right) as usize;
+                    let columns = (0..laps).map(|lap| lap * steps[i] % self.width);
+                    columns.filter(|&x| self.tree(x, 0)).count()
+                }
+                down if down < 0 => self.tree(0, 0) as usize,
+                _ => counts[i],
+            };
+        }
+        counts
+    }
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u8 = 3;
+    type Parsed<'a> = Map;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Map> {
+        Map::new(input)
+    }
+
+    fn part1(map: &Map) -> Result<usize> {
+        Ok(map.count_trees_traversed(Slope { right: 3, down: 1 }))
+    }
+
+    fn part2(map: &Map) -> Result<usize> {
+        Ok(map.count_trees_together(vec![
+            Slope { right: 1, down: 1 },
+            Slope { right: 3, down: 1 },
+            Slope { right: 5, down: 1 },
+            Slope { right: 7, down: 1 },
+            Slope { right: 1, down: 2 },
+        ]))
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::input::bundled;
 
     #[test]
     pub fn part1() {
-        let raw = include_str!("../inputs/day3.txt");
-        let map = Map::new(&raw);
+        let raw = &bundled("day3.txt");
+        let map = Map::new(raw).unwrap();
         let slope = Slope { right: 3, down: 1 };
         assert_eq!(map.count_trees_traversed(slope), 209);
     }
 
     #[test]
     pub fn example_part2() {
-        let raw = include_str!("../inputs/day3_example.txt");
-        let map = Map::new(&raw);
+        let raw = &bundled("day3_example.txt");
+        let map = Map::new(raw).unwrap();
         let slopes: Vec<Slope> = vec![
             Slope { right: 1, down: 1 },
             Slope { right: 3, down: 1 },
@@ -82,8 +328,8 @@ pub mod tests {
 
     #[test]
     pub fn part2() {
-        let raw = include_str!("../inputs/day3.txt");
-        let map = Map::new(&raw);
+        let raw = &bundled("day3.txt");
+        let map = Map::new(raw).unwrap();
         let slopes: Vec<Slope> = vec![
             Slope { right: 1, down: 1 },
             Slope { right: 3, down: 1 },
@@ -93,4 +339,127 @@ pub mod tests {
         ];
         assert_eq!(map.count_trees_together(slopes), 1574890240);
     }
+
+    #[test]
+    pub fn paths() {
+        let map = Map::new(&bundled("day3_example.txt")).unwrap();
+        let path = map.path(Slope { right: 3, down: 1 });
+        assert_eq!(path.len(), 11);
+        assert_eq!(path[1].position, Point::new(3, 1));
+        assert_eq!(path.iter().filter(|visit| visit.tree).count(), 7);
+        let rendered = map.render(Slope { right: 3, down: 1 });
+        let rows: Vec<&str> = rendered.lines().collect();
+        assert_eq!(rows.len(), 11);
+        assert_eq!(rows[0], "O.##.........##.........##.......");
+        assert_eq!(rows[1], "#..O#...#..#...#...#..#...#...#..");
+        assert_eq!(rows[2], ".#....X..#..#....#..#..#....#..#.");
+        assert_eq!(rows[10], ".#..#...#.#.#..#...#.#.#..#...X.#");
+
+        let left = map.path(Slope { right: -3, down: 1 });
+        assert_eq!(left[1].position, Point::new(-3, 1));
+        assert!(map
+            .render(Slope { right: -3, down: 1 })
+            .starts_with("..##.........##.........##.......O.##.......\n"));
+        //Mirrored around the first column, so -x lands where x did
+        let mirrored: String = bundled("day3_example.txt")
+            .lines()
+            .map(|row| {
+                format!(
+                    "{}{}\n",
+                    &row[..1],
+                    row[1..].chars().rev().collect::<String>()
+                )
+            })
+            .collect();
+        let mirrored = Map::new(&mirrored).unwrap();
+        for right in 1..5 {
+            assert_eq!(
+                map.count_trees_traversed(Slope {
+                    right: -right,
+                    down: 1
+                }),
+                mirrored.count_trees_traversed(Slope { right, down: 1 })
+            );
+        }
+
+        //Half a cell per row only lands on even rows
+        let half = Slope::rational(1, 2).unwrap();
+        assert_eq!(half, Slope::rational(-3, -6).unwrap());
+        assert_eq!(map.path(half), map.path(Slope { right: 1, down: 2 }));
+        assert_eq!(Slope::rational(6, 4), Some(Slope { right: 3, down: 2 }));
+        assert_eq!(Slope::rational(1, 0), None);
+
+        //11 rows don't split into steps of 3 and are still all counted
+        assert_eq!(map.path(Slope { right: 1, down: 3 }).len(), 4);
+        assert_eq!(map.path(Slope { right: 4, down: -1 }).len(), 1);
+        assert_eq!(map.path(Slope { right: 2, down: 0 }).len(), 11);
+        assert_eq!(map.path(Slope { right: 0, down: 0 }).len(), 1);
+    }
+
+    #[test]
+    pub fn rank_slopes() {
+        let map = Map::new(&bundled("day3_example.txt")).unwrap();
+        let ranked = map.rank_slopes(-12..=12, 0..=3, Goal::FewestTrees);
+        assert_eq!(ranked.len(), 25 * 4);
+        for r in &ranked {
+            assert_eq!(r.trees, map.count_trees_traversed(r.slope), "{:?}", r.slope);
+        }
+        assert!(ranked.windows(2).all(|pair| pair[0].trees <= pair[1].trees));
+        assert_eq!(ranked[0].trees, 0);
+
+        let ranked = map.rank_slopes(1..=7, 1..=2, Goal::MostTrees);
+        let best = Ranked {
+            slope: Slope { right: 3, down: 1 },
+            trees: 7,
+        };
+        assert_eq!(ranked[0], best);
+        assert!(ranked.windows(2).all(|pair| pair[0].trees >= pair[1].trees));
+
+        //Every right shares a single walk down the rows
+        let mut walks = Vec::new();
+        let ranked =
+            map.rank_slopes_walking(1..=7, 1..=3, Goal::FewestTrees, |down| walks.push(down));
+        assert_eq!(ranked.len(), 7 * 3);
+        assert_eq!(walks, vec![1, 2, 3]);
+    }
+
+    #[test]
+    pub fn packed_map() {
+        let slopes: Vec<Slope> = (-70..=70)
+            .flat_map(|right| (-1..=3).map(move |down| Slope { right, down }))
+            .collect();
+        //The example, the input, and a map with rows of three words
+        let wide: String = (0..90)
+            .map(|y| (0..150).map(move |x| if (x * 7 + y * 3) % 5 == 0 { '#' } else { '.' }))
+            .map(|row| row.collect::<String>() + "\n")
+            .collect();
+        for input in [bundled("day3_example.txt"), bundled("day3.txt"), wide] {
+            let map = Map::new(&input).unwrap();
+            let packed = map.packed();
+            let counts: Vec<usize> = slopes
+                .iter()
+                .map(|&s| map.count_trees_traversed(s))
+                .collect();
+            assert_eq!(packed.count_trees_batch(&slopes), counts);
+            assert_eq!(
+                packed.count_trees(Slope { right: 3, down: 1 }),
+                counts[73 * 5 + 2]
+            );
+        }
+    }
+
+    #[test]
+    pub fn malformed_map() {
+        let error = Map::new("..#\n.O.").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 2: expected `.` or `#`, found `O`"
+        );
+        let error = Map::new("..#\n.#").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 1: expected rows of 3 cells"
+        );
+        assert!(Map::new("").is_err());
+    }
 }
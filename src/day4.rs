@@ -1,92 +1,378 @@
+use crate::error::Result;
+use crate::input::normalize;
+use crate::parse::blocks;
+use crate::schema::{Diagnosis, Schema, Value};
+use crate::Solution;
 use itertools::Itertools;
+use serde::Serialize;
 use std::collections::HashMap;
+use std::sync::OnceLock;
 
-fn valid_passports(raw: &str) -> usize {
-    let fields = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
-    raw.lines()
-        .group_by(|line| *line != "")
-        .into_iter()
-        .filter(|(key, _group)| *key)
-        .map(|(_key, group)| group.collect::<Vec<&str>>().concat())
-        .filter(|group| fields.iter().all(|field| group.contains(field)))
-        .count()
+/// The passport rules from the puzzle, see `schemas/passport.toml`.
+pub fn passport_schema() -> &'static Schema {
+    static SCHEMA: OnceLock<Schema> = OnceLock::new();
+    SCHEMA.get_or_init(|| {
+        include_str!("../schemas/passport.toml")
+            .parse()
+            .expect("the bundled passport schema is valid")
+    })
 }
 
-fn valid_passports_part2(raw: &str) -> usize {
-    let fields = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
-    let passports: Vec<String> = raw
-        .lines()
-        .group_by(|line| *line != "")
-        .into_iter()
-        .filter(|(key, _group)| *key)
-        .map(|(_key, group)| group.collect::<Vec<&str>>().join(" "))
-        .filter(|group| fields.iter().all(|field| group.contains(field)))
-        .collect();
+/// A passport's fields as written, and read as numbers, measures or text by
+/// the schema it was parsed with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Passport<'a> {
+    /// The line the passport starts on.
+    pub line: usize,
+    pub fields: HashMap<&'a str, &'a str>,
+    /// The fields the schema knows about, read the way its rules need them.
+    pub values: HashMap<&'a str, Value<'a>>,
+    /// Keys given more than once, only kept when parsing leniently.
+    pub repeated: Vec<&'a str>,
+}
 
-    let mapped_passports: Vec<_> = passports
+impl<'a> Passport<'a> {
+    /// A field the schema reads as a number, like `byr`, if it is one.
+    pub fn number(&self, key: &str) -> Option<i64> {
+        match self.values.get(key)? {
+            Value::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// `hgt` as an amount and its unit, if it has one of the schema's units.
+    pub fn measure(&self, key: &str) -> Option<(i64, &'a str)> {
+        match self.values.get(key)? {
+            Value::Measure { amount, unit } => Some((*amount, *unit)),
+            _ => None,
+        }
+    }
+
+    pub fn is_complete(&self, schema: &Schema) -> bool {
+        schema.required().all(|key| self.fields.contains_key(key))
+    }
+
+    /// Whether every field follows its rules, given the schema the passport
+    /// was parsed with.
+    pub fn is_valid(&self, schema: &Schema) -> bool {
+        self.is_complete(schema)
+            && self.fields.iter().all(|(key, value)| {
+                match (schema.field(key), self.values.get(key)) {
+                    (Some(field), Some(&read)) => field.accepts_read(value, read),
+                    _ => schema.allow_unknown,
+                }
+            })
+    }
+}
+
+fn passports<'a>(raw: &'a str, schema: &Schema, strict: bool) -> Result<Vec<Passport<'a>>> {
+    //Passports are separated by blank lines. When not strict any field goes,
+    //and repeated ones keep their first value
+    let mut passports = Vec::new();
+    for block in blocks(raw) {
+        let mut fields = HashMap::new();
+        let mut repeated = Vec::new();
+        for line in &block {
+            for (key, value) in line.pairs(':')? {
+                if strict && !schema.allows(key) {
+                    return Err(line.error(key, format!("unknown field `{}`", key)));
+                }
+                if fields.contains_key(key) {
+                    if strict {
+                        return Err(line.error(key, format!("repeated field `{}`", key)));
+                    }
+                    if !repeated.contains(&key) {
+                        repeated.push(key);
+                    }
+                    continue;
+                }
+                fields.insert(key, value);
+            }
+        }
+        let values = fields
+            .iter()
+            .filter_map(|(&key, &value)| Some((key, schema.field(key)?.read(value))))
+            .collect();
+        let line = block[0].number;
+        passports.push(Passport {
+            line,
+            fields,
+            values,
+            repeated,
+        });
+    }
+    Ok(passports)
+}
+
+fn valid_passports(passports: &[Passport]) -> usize {
+    let schema = passport_schema();
+    passports.iter().filter(|p| p.is_complete(schema)).count()
+}
+
+fn valid_passports_part2(passports: &[Passport]) -> usize {
+    let schema = passport_schema();
+    passports.iter().filter(|p| p.is_valid(schema)).count()
+}
+
+/// Documents in the passport format, blocks of `key:value` fields, valid under
+/// any schema.
+pub fn count_valid(raw: &str, schema: &Schema) -> Result<usize> {
+    let raw = normalize(raw);
+    Ok(passports(&raw, schema, true)?
+        .iter()
+        .filter(|p| p.is_valid(schema))
+        .count())
+}
+
+/// What's wrong with one passport, numbered from 1, and the line it starts on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Report {
+    pub passport: usize,
+    pub line: usize,
+    pub valid: bool,
+    #[serde(flatten)]
+    pub diagnosis: Diagnosis,
+}
+
+/// Diagnoses every passport. Unknown and repeated fields are reported instead
+/// of failing.
+pub fn diagnose(raw: &str, schema: &Schema) -> Result<Vec<Report>> {
+    let raw = normalize(raw);
+    let reports = passports(&raw, schema, false)?
         .iter()
-        .map(|p| {
-            p.split_whitespace()
-                .flat_map(|p| p.split(':'))
-                .tuples()
-                .collect::<HashMap<_, _>>()
+        .enumerate()
+        .map(|(i, passport)| {
+            let mut diagnosis = schema.diagnose(&passport.fields);
+            diagnosis.repeated = passport
+                .repeated
+                .iter()
+                .map(|key| key.to_string())
+                .collect();
+            Report {
+                passport: i + 1,
+                line: passport.line,
+                valid: diagnosis.is_valid(),
+                diagnosis,
+            }
         })
         .collect();
+    Ok(reports)
+}
 
-    mapped_passports
+/// How many passports have each kind of problem, most common first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Failure {
+    pub reason: String,
+    pub passports: usize,
+}
+
+pub fn summarize(reports: &[Report]) -> Vec<Failure> {
+    let counts = reports
         .iter()
-        .filter(|p| is_valid_passport(p))
-        .count()
+        .flat_map(|report| report.diagnosis.kinds().into_iter().unique())
+        .counts();
+    counts
+        .into_iter()
+        .map(|(reason, passports)| Failure { reason, passports })
+        .sorted_by(|a, b| b.passports.cmp(&a.passports).then(a.reason.cmp(&b.reason)))
+        .collect()
 }
 
-fn is_valid_passport(passport: &HashMap<&str, &str>) -> bool {
-    passport.iter().all(|(&k, v)| match k {
-        "byr" => (1920..=2002).contains(&v.parse().unwrap_or(0)),
-        "iyr" => (2010..=2020).contains(&v.parse().unwrap_or(0)),
-        "eyr" => (2020..=2030).contains(&v.parse().unwrap_or(0)),
-        "hcl" => {
-            v.starts_with('#') && v.len() == 7 && v.chars().skip(1).all(|c| c.is_ascii_hexdigit())
-        }
-        "ecl" => ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"].contains(v),
-        "pid" => v.len() == 9 && v.chars().all(|c| c.is_ascii_digit()),
-        "cid" => true,
-        "hgt" => {
-            let height = v[0..(v.len() - 2)].parse().unwrap_or(0);
-            match &v[(v.len() - 2)..] {
-                "cm" => (150..=193).contains(&height),
-                "in" => (59..=76).contains(&height),
-                _ => false,
-            }
-        }
-        _ => unreachable!(),
-    })
+pub fn reports_to_table(reports: &[Report], summary: &[Failure]) -> String {
+    let mut table = String::from("Passport  Line  Verdict  Problems\n");
+    for report in reports {
+        let row = format!(
+            "{:>8}  {:>4}  {:<7}  {}",
+            report.passport,
+            report.line,
+            if report.valid { "valid" } else { "invalid" },
+            report.diagnosis.problems().join("; ")
+        );
+        table.push_str(row.trim_end());
+        table.push('\n');
+    }
+    table.push_str("\nPassports  Reason\n");
+    for failure in summary {
+        table.push_str(&format!("{:>9}  {}\n", failure.passports, failure.reason));
+    }
+    table
+}
+
+pub fn reports_to_json(reports: &[Report], summary: &[Failure]) -> String {
+    let json = serde_json::json!({ "passports": reports, "summary": summary });
+    serde_json::to_string_pretty(&json).expect("reports are plain data")
+}
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    const DAY: u8 = 4;
+    type Parsed<'a> = Vec<Passport<'a>>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Vec<Passport<'_>>> {
+        passports(input, passport_schema(), true)
+    }
+
+    fn part1(passports: &Vec<Passport>) -> Result<usize> {
+        Ok(valid_passports(passports))
+    }
+
+    fn part2(passports: &Vec<Passport>) -> Result<usize> {
+        Ok(valid_passports_part2(passports))
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::input::bundled;
+
+    fn parse(raw: &str) -> Vec<Passport<'_>> {
+        passports(raw, passport_schema(), true).unwrap()
+    }
 
     #[test]
     pub fn example_part1() {
-        let raw = include_str!("../inputs/day4_example1.txt");
-        assert_eq!(valid_passports(raw), 2);
+        let raw = &bundled("day4_example1.txt");
+        assert_eq!(valid_passports(&parse(raw)), 2);
     }
 
     #[test]
     pub fn part1() {
-        let raw = include_str!("../inputs/day4.txt");
-        assert_eq!(valid_passports(raw), 256);
+        let raw = &bundled("day4.txt");
+        assert_eq!(valid_passports(&parse(raw)), 256);
     }
 
     #[test]
     pub fn example_part2() {
-        let raw = include_str!("../inputs/day4_example2.txt");
-        assert_eq!(valid_passports_part2(raw), 4);
+        let raw = &bundled("day4_example2.txt");
+        assert_eq!(valid_passports_part2(&parse(raw)), 4);
     }
 
     #[test]
     pub fn part2() {
-        let raw = include_str!("../inputs/day4.txt");
-        assert_eq!(valid_passports_part2(raw), 198);
+        let raw = &bundled("day4.txt");
+        assert_eq!(valid_passports_part2(&parse(raw)), 198);
+    }
+
+    #[test]
+    pub fn keys_in_values() {
+        //Each one lacks a field named in some value
+        let raw = "hcl:#byr123 iyr:2015 eyr:2025 hgt:170cm ecl:brn pid:000000001\n\n\
+                   byr:1990 iyr:2015 hgt:170cm hcl:#123abc ecl:brn pid:000000001 cid:eyr\n\n\
+                   byr:1990 iyr:2015 eyr:2025 hgt:170cm hcl:#123abc ecl:brn\ncid:pid";
+        let parsed = parse(raw);
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(valid_passports(&parsed), 0);
+        assert_eq!(valid_passports_part2(&parsed), 0);
+        //Before, part 1 joined the lines and found `hgt` across both
+        let raw = "byr:1990 iyr:2015 eyr:2025 hcl:#123abc ecl:brn pid:000000001 cid:h\ngt";
+        assert!(passports(raw, passport_schema(), true).is_err());
+        let raw = "byr:1990 iyr:2015 eyr:2025 hcl:#123abc ecl:brn pid:000000001\ncid:hg hgt:70in";
+        assert_eq!(valid_passports_part2(&parse(raw)), 1);
+    }
+
+    #[test]
+    pub fn typed_values() {
+        let passports = parse("byr:1990 hgt:170cm\n\nbyr:x\nhgt:70in\n\nhgt:70 hcl:#123abc");
+        assert_eq!(passports[0].number("byr"), Some(1990));
+        assert_eq!(passports[0].measure("hgt"), Some((170, "cm")));
+        assert_eq!(passports[1].number("byr"), None);
+        assert_eq!(passports[1].fields["byr"], "x");
+        assert_eq!(passports[1].measure("hgt"), Some((70, "in")));
+        assert_eq!(passports[2].measure("hgt"), None);
+        assert_eq!(passports[2].values["hcl"], Value::Text("#123abc"));
+        assert_eq!(passports[2].line, 6);
+    }
+
+    #[test]
+    pub fn malformed_passports() {
+        let schema = passport_schema();
+        let error =
+            passports("ecl:gry pid:860033327\n\nhgt:183cm foo:bar", schema, true).unwrap_err();
+        assert_eq!(error.to_string(), "line 3, column 11: unknown field `foo`");
+        let error = passports("ecl:gry pid", schema, true).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 9: expected `key:value`, found `pid`"
+        );
+        let error = passports("byr:1990 hgt:70in\nbyr:x", schema, true).unwrap_err();
+        assert_eq!(error.to_string(), "line 2, column 1: repeated field `byr`");
+        assert!(count_valid("byr:1990 byr:x", schema).is_err());
+        let passport = &parse("hgt:cm")[0];
+        assert!(!passport.is_valid(schema));
+    }
+
+    #[test]
+    pub fn diagnostics() {
+        let raw = "hgt:190in byr:1900\niyr:2015\n\nfoo:bar\nbyr:2000\n\n".to_string()
+            + "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980\nhcl:#623a2f";
+        let reports = diagnose(&raw, passport_schema()).unwrap();
+        assert_eq!(reports.len(), 3);
+        assert_eq!((reports[1].passport, reports[1].line), (2, 4));
+        assert!(reports[2].valid);
+        assert_eq!(
+            reports[0].diagnosis.problems()[3..],
+            [
+                "missing pid",
+                "byr=1900: 1900 not in 1920..=2002",
+                "hgt=190in: 190 not in 59..=76"
+            ]
+        );
+        assert_eq!(reports[1].diagnosis.unknown, ["foo"]);
+
+        //The first value is checked, the others only reported
+        let repeated = diagnose("byr:1990 byr:x\nbyr:1 hgt:70in hgt:x", passport_schema()).unwrap();
+        let diagnosis = &repeated[0].diagnosis;
+        assert!(!repeated[0].valid);
+        assert_eq!(diagnosis.repeated, ["byr", "hgt"]);
+        assert!(diagnosis.invalid.is_empty());
+        assert_eq!(diagnosis.problems().last().unwrap(), "repeated hgt");
+        assert!(summarize(&repeated).contains(&Failure {
+            reason: "byr: repeated".to_string(),
+            passports: 1,
+        }));
+        let summary = summarize(&reports);
+        let failure = |reason: &str, passports| Failure {
+            reason: reason.to_string(),
+            passports,
+        };
+        assert_eq!(summary[0], failure("ecl: missing", 2));
+        assert!(summary.contains(&failure("hgt: units", 1)));
+        assert!(summary.contains(&failure("foo: unknown", 1)));
+        assert_eq!(summary.iter().map(|f| f.passports).sum::<usize>(), 6 + 7);
+
+        let table = reports_to_table(&reports, &summary);
+        assert!(table.contains("\n       3     7  valid\n"));
+        assert!(table.contains("\n        2  ecl: missing\n"));
+        let json: serde_json::Value =
+            serde_json::from_str(&reports_to_json(&reports, &summary)).unwrap();
+        assert_eq!(json["passports"][0]["invalid"][1]["rule"], "units");
+        assert_eq!(json["passports"][1]["unknown"][0], "foo");
+        assert_eq!(json["summary"][0]["passports"], 2);
+
+        //Every valid passport of the input, and only those
+        let raw = &bundled("day4.txt");
+        let reports = diagnose(raw, passport_schema()).unwrap();
+        assert_eq!(reports.iter().filter(|r| r.valid).count(), 198);
+    }
+
+    #[test]
+    pub fn other_schemas() {
+        //Passports that only need a birth year, any of them
+        let schema: Schema = "allow_unknown = true\n[fields.byr]\n".parse().unwrap();
+        let raw = &bundled("day4_example2.txt");
+        assert_eq!(count_valid(raw, &schema).unwrap(), 8);
+        assert_eq!(count_valid("foo:bar\n\nbyr:1", &schema).unwrap(), 1);
+        let strict: Schema = "[fields.byr]\nrange = [1920, 2002]".parse().unwrap();
+        assert!(count_valid(raw, &strict).is_err());
+
+        //Same count as the diagnosis, whatever the line endings
+        let crlf = "\u{feff}".to_string() + &bundled("day4.txt").replace('\n', "\r\n");
+        assert_eq!(count_valid(&crlf, passport_schema()).unwrap(), 198);
+        let reports = diagnose(&crlf, passport_schema()).unwrap();
+        assert_eq!(reports.iter().filter(|r| r.valid).count(), 198);
     }
 }
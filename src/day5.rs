@@ -1,3 +1,6 @@
+use crate::error::{AocError, Result};
+use crate::parse::lines;
+use crate::Solution;
 use std::ops::Range;
 
 trait Halving {
@@ -21,45 +24,92 @@ struct Seat {
 }
 
 impl Seat {
-    fn new(location: &str) -> Self {
+    fn new(location: &str) -> Result<Self> {
         let mut seat = Seat {
             row: (0..127),
             column: (0..7),
             id: 0,
         };
-        for c in location.chars().take(7) {
-            match c {
-                'F' => seat.row.lower_half(),
-                'B' => seat.row.upper_half(),
-                _ => unreachable!(),
+        for (i, c) in location.chars().enumerate() {
+            match (i, c) {
+                (0..=6, 'F') => seat.row.lower_half(),
+                (0..=6, 'B') => seat.row.upper_half(),
+                (7..=9, 'L') => seat.column.lower_half(),
+                (7..=9, 'R') => seat.column.upper_half(),
+                (0..=6, _) => {
+                    return Err(AocError::parse(
+                        1,
+                        i + 1,
+                        format!("expected `F` or `B`, found `{}`", c),
+                    ))
+                }
+                (7..=9, _) => {
+                    return Err(AocError::parse(
+                        1,
+                        i + 1,
+                        format!("expected `L` or `R`, found `{}`", c),
+                    ))
+                }
+                _ => return Err(AocError::parse(1, i + 1, "expected 10 characters")),
             }
         }
-        for c in location.chars().skip(7) {
-            match c {
-                'L' => seat.column.lower_half(),
-                'R' => seat.column.upper_half(),
-                _ => unreachable!(),
-            }
+        if location.chars().count() < 10 {
+            let message = "expected 10 characters";
+            return Err(AocError::parse(1, location.chars().count() + 1, message));
         }
         seat.id = seat.row.start * 8 + seat.column.start;
-        seat
+        Ok(seat)
     }
 }
 
-fn highest_id_in_boarding_pass(input: &str) -> usize {
-    input.lines().map(|l| Seat::new(l).id).max().unwrap()
+fn seat_ids(input: &str) -> Result<Vec<usize>> {
+    lines(input)
+        .map(|l| {
+            Seat::new(l.text)
+                .map(|seat| seat.id)
+                .map_err(|e| l.locate(l.text, e))
+        })
+        .collect()
+}
+
+fn highest_id_in_boarding_pass(ids: &[usize]) -> Result<usize> {
+    ids.iter()
+        .max()
+        .copied()
+        .ok_or_else(|| AocError::no_solution("there are no boarding passes"))
 }
 
-fn find_your_seat(input: &str) -> usize {
-    let ids: Vec<usize> = input.lines().map(|l| Seat::new(l).id).collect();
+fn find_your_seat(ids: &[usize]) -> Result<usize> {
     (1..1023)
         .find(|id| !ids.contains(id) && ids.contains(&(id - 1)) && ids.contains(&(id + 1)))
-        .unwrap()
+        .ok_or_else(|| AocError::no_solution("there is no free seat between two taken ones"))
+}
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: u8 = 5;
+    type Parsed<'a> = Vec<usize>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Vec<usize>> {
+        seat_ids(input)
+    }
+
+    fn part1(ids: &Vec<usize>) -> Result<usize> {
+        highest_id_in_boarding_pass(ids)
+    }
+
+    fn part2(ids: &Vec<usize>) -> Result<usize> {
+        find_your_seat(ids)
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::input::bundled;
 
     #[test]
     fn example_part1() {
@@ -67,9 +117,9 @@ pub mod tests {
         let location2 = "FFFBBBFRRR";
         let location3 = "BBFFBBFRLL";
 
-        let seat1 = Seat::new(location1);
-        let seat2 = Seat::new(location2);
-        let seat3 = Seat::new(location3);
+        let seat1 = Seat::new(location1).unwrap();
+        let seat2 = Seat::new(location2).unwrap();
+        let seat3 = Seat::new(location3).unwrap();
 
         assert_eq!(seat1.row.start, 70);
         assert_eq!(seat1.column.start, 7);
@@ -86,13 +136,40 @@ pub mod tests {
 
     #[test]
     fn part1() {
-        let input = include_str!("../inputs/day5.txt");
-        assert_eq!(highest_id_in_boarding_pass(input), 987);
+        let input = &bundled("day5.txt");
+        assert_eq!(
+            highest_id_in_boarding_pass(&seat_ids(input).unwrap()).unwrap(),
+            987
+        );
     }
 
     #[test]
     fn part2() {
-        let input = include_str!("../inputs/day5.txt");
-        assert_eq!(find_your_seat(input), 603);
+        let input = &bundled("day5.txt");
+        assert_eq!(find_your_seat(&seat_ids(input).unwrap()).unwrap(), 603);
+    }
+
+    #[test]
+    fn malformed_boarding_passes() {
+        let error = seat_ids("BFFFBBFRRR\nFFFBBBFRXR").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 9: expected `L` or `R`, found `X`"
+        );
+        let error = seat_ids("BFFFBBRRRR").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 7: expected `F` or `B`, found `R`"
+        );
+        let error = seat_ids("BFFFBBFRR").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 10: expected 10 characters"
+        );
+        let error = seat_ids("BFFFBBFRRRL").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 11: expected 10 characters"
+        );
     }
 }
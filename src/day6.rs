@@ -1,52 +1,144 @@
+use crate::error::Result;
+use crate::parse::blocks;
+use crate::Solution;
 use itertools::Itertools;
 
-fn count_unique_answers(input: &str) -> usize {
-    input
-        .split("\n\n")
-        .map(|group| group.replace("\n", "").chars().unique().count())
+fn count_unique_answers(groups: &[Vec<&str>]) -> usize {
+    groups
+        .iter()
+        .map(|group| group.concat().chars().unique().count())
         .sum()
 }
 
-fn count_answers_common_everyone(input: &str) -> usize {
-    input
-        .split("\n\n")
+fn count_answers_common_everyone(groups: &[Vec<&str>]) -> usize {
+    groups
+        .iter()
         .map(|group| {
             group
-                .lines()
-                .next()
-                .unwrap()
+                .first()
+                .copied()
+                .unwrap_or_default()
                 .chars()
-                .filter(|c| group.lines().all(|line| line.contains(*c)))
+                .unique()
+                .filter(|c| group.iter().all(|person| person.contains(*c)))
                 .count()
         })
         .sum()
 }
 
+//Bitset version from hacks.md, a bit per question
+#[cfg(test)]
+fn count_answers_common_everyone_mask(groups: &[Vec<&str>]) -> usize {
+    groups
+        .iter()
+        .map(|group| {
+            group
+                .iter()
+                .map(|person| person.bytes().fold(0u32, |x, b| x | 1 << (b - b'a')))
+                .fold(!0, |acc, x| acc & x)
+                .count_ones() as usize
+        })
+        .sum()
+}
+
+fn groups(input: &str) -> Result<Vec<Vec<&str>>> {
+    //Groups are separated by blank lines, one person per line.
+    //Every question is answered with a letter from a to z
+    blocks(input)
+        .map(|group| {
+            group
+                .into_iter()
+                .map(|person| {
+                    let text = person.text;
+                    match text.char_indices().find(|(_i, c)| !c.is_ascii_lowercase()) {
+                        Some((i, c)) => {
+                            let message =
+                                format!("expected a question from `a` to `z`, found `{}`", c);
+                            Err(person.error(&text[i..], message))
+                        }
+                        None => Ok(text),
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u8 = 6;
+    type Parsed<'a> = Vec<Vec<&'a str>>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Vec<Vec<&str>>> {
+        groups(input)
+    }
+
+    fn part1(groups: &Vec<Vec<&str>>) -> Result<usize> {
+        Ok(count_unique_answers(groups))
+    }
+
+    fn part2(groups: &Vec<Vec<&str>>) -> Result<usize> {
+        Ok(count_answers_common_everyone(groups))
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::input::bundled;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
 
     #[test]
     fn example_part1() {
-        let input = include_str!("../inputs/day6_example1.txt");
-        assert_eq!(count_unique_answers(input), 11);
+        let input = &bundled("day6_example1.txt");
+        assert_eq!(count_unique_answers(&groups(input).unwrap()), 11);
     }
 
     #[test]
     fn part1() {
-        let input = include_str!("../inputs/day6.txt");
-        assert_eq!(count_unique_answers(input), 6885);
+        let input = &bundled("day6.txt");
+        assert_eq!(count_unique_answers(&groups(input).unwrap()), 6885);
     }
 
     #[test]
     pub fn example_part2() {
-        let input = include_str!("../inputs/day6_example1.txt");
-        assert_eq!(count_answers_common_everyone(input), 6);
+        let input = &bundled("day6_example1.txt");
+        assert_eq!(count_answers_common_everyone(&groups(input).unwrap()), 6);
     }
 
     #[test]
     pub fn part2() {
-        let input = include_str!("../inputs/day6.txt");
-        assert_eq!(count_answers_common_everyone(input), 3550);
+        let input = &bundled("day6.txt");
+        assert_eq!(count_answers_common_everyone(&groups(input).unwrap()), 3550);
+    }
+
+    proptest! {
+        //Parsing never makes a group without people
+        #[test]
+        fn mask_version_agrees(groups in vec(vec("[a-z]{1,8}", 1..6), 0..10)) {
+            let groups: Vec<Vec<&str>> = groups
+                .iter()
+                .map(|group| group.iter().map(String::as_str).collect())
+                .collect();
+            prop_assert_eq!(
+                count_answers_common_everyone_mask(&groups),
+                count_answers_common_everyone(&groups)
+            );
+        }
+    }
+
+    #[test]
+    pub fn malformed_answers() {
+        let error = groups("abc\n\na b").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 3, column 2: expected a question from `a` to `z`, found ` `"
+        );
+        assert_eq!(count_answers_common_everyone(&[vec![]]), 0);
+        assert_eq!(count_answers_common_everyone(&[vec!["nn"]]), 1);
     }
 }
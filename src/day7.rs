@@ -1,45 +1,44 @@
+use crate::error::{AocError, Result};
+use crate::parse::{lines, Line};
+use crate::Solution;
 use petgraph::graphmap::DiGraphMap;
 use petgraph::visit::Walker;
 
-use itertools::izip;
-use std::iter;
-
-fn get_edges_with_weights(rule: &str) -> impl Iterator<Item = (&str, &str, u32)> {
-    //Parse a line of the input, and gives back an iterator of tuples of nodes(bags) including weights.
-    //Dont give it input of rules that contains the text "contain no".
-    let mut iter_parts = rule.split(" bags contain ");
-    let (parent, rest) = (iter_parts.next().unwrap(), iter_parts.next().unwrap());
-    let parent_iter = iter::repeat(parent);
-    let rest_iter = rest
-        .split(char::is_numeric)
-        .skip(1)
-        .map(|s| s.trim().rsplitn(2, ' ').skip(1).next().unwrap());
-    let weights_iter = rest
-        .chars()
-        .filter(|c| c.is_numeric())
-        .map(|c| c.to_digit(10).unwrap());
-
-    let edges_iterator = izip!(parent_iter, rest_iter, weights_iter);
-    edges_iterator
+fn get_edges_with_weights(rule: Line<'_>) -> Result<Vec<(&str, &str, u32)>> {
+    //Parse a line of the input, and gives back the tuples of nodes(bags) including weights.
+    //Rules that contain "no other bags" give no edges.
+    let [parent, contents] = rule.fields("{color} bags contain {contents}.")?;
+    if contents == "no other bags" {
+        return Ok(Vec::new());
+    }
+    contents
+        .split(", ")
+        .map(|content| {
+            //One bag, or several bags
+            let content = content.strip_suffix('s').unwrap_or(content);
+            let [weight, child] = Line::new(1, content)
+                .fields("{number} {color} bag")
+                .map_err(|e| rule.locate(content, e))?;
+            Ok((parent, child, rule.parse(weight)?))
+        })
+        .collect()
 }
 
-fn build_graph(input: &str) -> DiGraphMap<&str, u32> {
-    //Return the number of bag colors that can contain one shiny gold.
+fn build_graph(input: &str) -> Result<DiGraphMap<&str, u32>> {
     // Create a new directed GraphMap.
-    let edges = input
-        .lines()
-        .filter(|rule| !rule.contains(" contain no "))
-        .flat_map(|rule| get_edges_with_weights(rule));
-    DiGraphMap::<_, u32>::from_edges(edges)
+    let mut edges = Vec::new();
+    for rule in lines(input) {
+        edges.extend(get_edges_with_weights(rule)?);
+    }
+    Ok(DiGraphMap::<_, u32>::from_edges(edges))
 }
 
-fn bags_can_contain(gr: DiGraphMap<&str, u32>) -> usize {
+fn bags_can_contain(gr: &DiGraphMap<&str, u32>) -> usize {
     //Return the number of bag colors that can contain one shiny gold.
-    let count = petgraph::visit::Dfs::new(&gr, "shiny gold")
-        .iter(petgraph::visit::Reversed(&gr))
+    petgraph::visit::Dfs::new(gr, "shiny gold")
+        .iter(petgraph::visit::Reversed(gr))
         .skip(1) // first is our starting node shiny gold
-        .count();
-    count
+        .count()
 }
 
 fn individual_bags_required(graph: &DiGraphMap<&str, u32>, start: &str, last_call: bool) -> u32 {
@@ -58,42 +57,87 @@ fn individual_bags_required(graph: &DiGraphMap<&str, u32>, start: &str, last_cal
     }
 }
 
+pub struct Day7;
+
+impl Solution for Day7 {
+    const DAY: u8 = 7;
+    type Parsed<'a> = DiGraphMap<&'a str, u32>;
+    type Answer1 = usize;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<DiGraphMap<&str, u32>> {
+        build_graph(input)
+    }
+
+    fn part1(graph: &DiGraphMap<&str, u32>) -> Result<usize> {
+        Ok(bags_can_contain(graph))
+    }
+
+    fn part2(graph: &DiGraphMap<&str, u32>) -> Result<u32> {
+        //A bag that ends up containing itself would need infinite bags
+        if petgraph::algo::is_cyclic_directed(graph) {
+            return Err(AocError::no_solution("some bags contain themselves"));
+        }
+        Ok(individual_bags_required(graph, "shiny gold", true))
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::input::bundled;
 
     #[test]
     fn example_part1() {
-        let input = include_str!("../inputs/day7_example1.txt");
-        let graph = build_graph(input);
-        assert_eq!(bags_can_contain(graph), 4);
+        let input = &bundled("day7_example1.txt");
+        let graph = build_graph(input).unwrap();
+        assert_eq!(bags_can_contain(&graph), 4);
     }
 
     #[test]
     fn part1() {
-        let input = include_str!("../inputs/day7.txt");
-        let graph = build_graph(input);
-        assert_eq!(bags_can_contain(graph), 151);
+        let input = &bundled("day7.txt");
+        let graph = build_graph(input).unwrap();
+        assert_eq!(bags_can_contain(&graph), 151);
     }
 
     #[test]
     fn example1_part2() {
-        let input = include_str!("../inputs/day7_example1.txt");
-        let graph = build_graph(input);
+        let input = &bundled("day7_example1.txt");
+        let graph = build_graph(input).unwrap();
         assert_eq!(individual_bags_required(&graph, "shiny gold", true), 32);
     }
 
     #[test]
     fn example2_part2() {
-        let input = include_str!("../inputs/day7_example2.txt");
-        let graph = build_graph(input);
+        let input = &bundled("day7_example2.txt");
+        let graph = build_graph(input).unwrap();
         assert_eq!(individual_bags_required(&graph, "shiny gold", true), 126);
     }
 
     #[test]
     fn part2() {
-        let input = include_str!("../inputs/day7.txt");
-        let graph = build_graph(input);
+        let input = &bundled("day7.txt");
+        let graph = build_graph(input).unwrap();
         assert_eq!(individual_bags_required(&graph, "shiny gold", true), 41559);
     }
+
+    #[test]
+    fn malformed_rules() {
+        let error = build_graph(
+            "faded blue bags contain no other bags.\nshiny gold bags hold 1 dark olive bag.",
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 1: expected `<color> bags contain <contents>.`"
+        );
+        let error = build_graph("shiny gold bags contain one dark olive bag.").unwrap_err();
+        assert_eq!(error.to_string(), "line 1, column 25: invalid number `one`");
+        let graph = build_graph(
+            "shiny gold bags contain 1 dark olive bag.\ndark olive bags contain 2 shiny gold bags.",
+        )
+        .unwrap();
+        assert!(Day7::part2(&graph).is_err());
+    }
 }
@@ -1,17 +1,19 @@
-use itertools::Itertools;
+use crate::error::{AocError, Result};
+use crate::parse::lines;
+use crate::Solution;
+use std::convert::TryFrom;
 
-fn boot_code(input: &str) -> Vec<(&str, i32, bool)> {
+fn boot_code(input: &str) -> Result<Vec<(&str, i32, bool)>> {
     //Returns a Vec of instructions(tuples of pairs operation:value)
     //The tuple include a bool to check if a given instruction has been visited.
-    input
-        .lines()
+    lines(input)
         .map(|instruction| {
-            let mut it = instruction.split(' ');
-            (
-                it.next().unwrap(),
-                it.next().unwrap().parse::<i32>().unwrap(),
-                false,
-            )
+            let [operation, argument] = instruction.fields("{operation} {argument}")?;
+            if !["acc", "jmp", "nop"].contains(&operation) {
+                let message = format!("unknown operation `{}`", operation);
+                return Err(instruction.error(operation, message));
+            }
+            Ok((operation, instruction.parse::<i32>(argument)?, false))
         })
         .collect()
 }
@@ -25,11 +27,15 @@ fn acc_before_repeat(instructions: Vec<(&str, i32, bool)>) -> (i32, bool) {
     //Instruction pointer
     let mut ip: i32 = 0;
     let mut repeated = false;
-    let mut terminated = false;
+    let mut terminated = instructions.is_empty();
 
     while !repeated && !terminated {
-        //Unpack the instruction
-        let (operation, argument, done) = instructions[ip as usize];
+        //Unpack the instruction, a jump outside the program crashes it
+        let Some(&(operation, argument, done)) =
+            usize::try_from(ip).ok().and_then(|ip| instructions.get(ip))
+        else {
+            break;
+        };
         if !done {
             //Update this instruction as done
             instructions[ip as usize].2 = true;
@@ -55,9 +61,9 @@ fn acc_before_repeat(instructions: Vec<(&str, i32, bool)>) -> (i32, bool) {
     (acc, terminated)
 }
 
-fn search_valid_change(instructions: Vec<(&str, i32, bool)>) -> i32 {
+fn search_valid_change(instructions: Vec<(&str, i32, bool)>) -> Result<i32> {
     //When you dont want to think brute-force is your friend
-    (0..=instructions.len() - 1)
+    (0..instructions.len())
         .rev()
         .filter(|index_changed| !instructions[*index_changed].0.contains("acc"))
         .map(|index_changed| {
@@ -71,35 +77,81 @@ fn search_valid_change(instructions: Vec<(&str, i32, bool)>) -> i32 {
             acc_before_repeat(instr)
         })
         .find(|(_acc, terminated)| *terminated)
-        .unwrap()
-        .0
+        .map(|(acc, _terminated)| acc)
+        .ok_or_else(|| AocError::no_solution("no single change makes the program terminate"))
+}
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: u8 = 8;
+    type Parsed<'a> = Vec<(&'a str, i32, bool)>;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    fn parse(input: &str) -> Result<Vec<(&str, i32, bool)>> {
+        boot_code(input)
+    }
+
+    fn part1(instructions: &Vec<(&str, i32, bool)>) -> Result<i32> {
+        Ok(acc_before_repeat(instructions.clone()).0)
+    }
+
+    fn part2(instructions: &Vec<(&str, i32, bool)>) -> Result<i32> {
+        search_valid_change(instructions.clone())
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::input::bundled;
 
     #[test]
     fn example_part1() {
-        let input = include_str!("../inputs/day8_example1.txt");
-        assert_eq!(acc_before_repeat(boot_code(input)).0, 5);
+        let input = &bundled("day8_example1.txt");
+        assert_eq!(acc_before_repeat(boot_code(input).unwrap()).0, 5);
     }
 
     #[test]
     fn part1() {
-        let input = include_str!("../inputs/day8.txt");
-        assert_eq!(acc_before_repeat(boot_code(input)).0, 2080);
+        let input = &bundled("day8.txt");
+        assert_eq!(acc_before_repeat(boot_code(input).unwrap()).0, 2080);
     }
 
     #[test]
     fn example_part2() {
-        let input = include_str!("../inputs/day8_example1.txt");
-        assert_eq!(search_valid_change(boot_code(input)), 8);
+        let input = &bundled("day8_example1.txt");
+        assert_eq!(search_valid_change(boot_code(input).unwrap()).unwrap(), 8);
     }
 
     #[test]
     fn part2() {
-        let input = include_str!("../inputs/day8.txt");
-        assert_eq!(search_valid_change(boot_code(input)), 2477);
+        let input = &bundled("day8.txt");
+        assert_eq!(
+            search_valid_change(boot_code(input).unwrap()).unwrap(),
+            2477
+        );
+    }
+
+    #[test]
+    fn malformed_boot_code() {
+        let error = boot_code("nop +0\nacc +x").unwrap_err();
+        assert_eq!(error.to_string(), "line 2, column 5: invalid number `+x`");
+        let error = boot_code("nop +0\nhcf +1").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 1: unknown operation `hcf`"
+        );
+        let error = boot_code("jmp").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 1: expected `<operation> <argument>`"
+        );
+        //Jumping outside the program stops it without terminating
+        assert_eq!(
+            acc_before_repeat(boot_code("acc +1\njmp -7").unwrap()),
+            (1, false)
+        );
     }
 }
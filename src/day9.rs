@@ -1,17 +1,18 @@
+use crate::error::{AocError, Result};
+use crate::parse::numbers;
+use crate::Solution;
 use itertools::Itertools;
 
-fn parse_input(input: &str) -> Vec<u64> {
-    input
-        .lines()
-        .map(|line| line.parse::<u64>().unwrap())
-        .collect()
+fn parse_input(input: &str) -> Result<Vec<u64>> {
+    numbers(input)
 }
 
-fn check_bad_data(encrypted_data: Vec<u64>, preamble_size: usize) -> u64 {
+fn check_bad_data(encrypted_data: &[u64], preamble_size: usize) -> Result<u64> {
     encrypted_data
         .windows(preamble_size + 1)
         .find(|window| !sum_of_two(window))
-        .unwrap()[preamble_size]
+        .map(|window| window[preamble_size])
+        .ok_or_else(|| AocError::no_solution("every number is the sum of two previous ones"))
 }
 
 fn sum_of_two(numbers: &[u64]) -> bool {
@@ -23,8 +24,8 @@ fn sum_of_two(numbers: &[u64]) -> bool {
         .is_some()
 }
 
-fn encryption_weakness(encrypted_data: Vec<u64>, preamble_size: usize) -> u64 {
-    let invalid_number = check_bad_data(encrypted_data.clone(), preamble_size);
+fn encryption_weakness(encrypted_data: &[u64], preamble_size: usize) -> Result<u64> {
+    let invalid_number = check_bad_data(encrypted_data, preamble_size)?;
     let position = encrypted_data
         .iter()
         .position(|n| *n == invalid_number)
@@ -38,42 +39,72 @@ fn encryption_weakness(encrypted_data: Vec<u64>, preamble_size: usize) -> u64 {
             })
             .next();
         if let Some(data) = searched_chunk {
-            return *data.iter().max().unwrap() + *data.iter().min().unwrap();
+            return Ok(*data.iter().max().unwrap() + *data.iter().min().unwrap());
         }
     }
-    //Unreachable
-    0
+    Err(AocError::no_solution(
+        "no contiguous numbers add up to the invalid one",
+    ))
+}
+
+pub struct Day9;
+
+impl Solution for Day9 {
+    const DAY: u8 = 9;
+    type Parsed<'a> = Vec<u64>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Vec<u64>> {
+        parse_input(input)
+    }
+
+    fn part1(encrypted_data: &Vec<u64>) -> Result<u64> {
+        check_bad_data(encrypted_data, 25)
+    }
+
+    fn part2(encrypted_data: &Vec<u64>) -> Result<u64> {
+        encryption_weakness(encrypted_data, 25)
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::input::bundled;
 
     #[test]
     fn example_part1() {
-        let input = include_str!("../inputs/day9_example1.txt");
-        let encrypted_data = parse_input(input);
-        assert_eq!(check_bad_data(encrypted_data, 5), 127);
+        let input = &bundled("day9_example1.txt");
+        let encrypted_data = parse_input(input).unwrap();
+        assert_eq!(check_bad_data(&encrypted_data, 5).unwrap(), 127);
     }
 
     #[test]
     fn part1() {
-        let input = include_str!("../inputs/day9.txt");
-        let encrypted_data = parse_input(input);
-        assert_eq!(check_bad_data(encrypted_data, 25), 104054607);
+        let input = &bundled("day9.txt");
+        let encrypted_data = parse_input(input).unwrap();
+        assert_eq!(check_bad_data(&encrypted_data, 25).unwrap(), 104054607);
     }
 
     #[test]
     fn example_part2() {
-        let input = include_str!("../inputs/day9_example1.txt");
-        let encrypted_data = parse_input(input);
-        assert_eq!(encryption_weakness(encrypted_data, 5), 62);
+        let input = &bundled("day9_example1.txt");
+        let encrypted_data = parse_input(input).unwrap();
+        assert_eq!(encryption_weakness(&encrypted_data, 5).unwrap(), 62);
     }
 
     #[test]
     pub fn part2() {
-        let input = include_str!("../inputs/day9.txt");
-        let encrypted_data = parse_input(input);
-        assert_eq!(encryption_weakness(encrypted_data, 25), 13935797);
+        let input = &bundled("day9.txt");
+        let encrypted_data = parse_input(input).unwrap();
+        assert_eq!(encryption_weakness(&encrypted_data, 25).unwrap(), 13935797);
+    }
+
+    #[test]
+    fn malformed_data() {
+        let error = parse_input("35\n20\n-15").unwrap_err();
+        assert_eq!(error.to_string(), "line 3, column 1: invalid number `-15`");
+        assert!(check_bad_data(&[1, 2, 3, 5], 2).is_err());
     }
 }
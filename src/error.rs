@@ -0,0 +1,53 @@
+use crate::input::InputError;
+use std::fmt::{self, Display};
+
+pub type Result<T> = std::result::Result<T, AocError>;
+
+#[derive(Debug)]
+pub enum AocError {
+    /// The input doesn't look like the puzzle says. Lines and columns start at 1.
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// The input is well formed but there is no answer for it.
+    NoSolution(String),
+    Input(InputError),
+}
+
+impl AocError {
+    pub fn parse(line: usize, column: usize, message: impl Into<String>) -> Self {
+        AocError::Parse {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    pub fn no_solution(message: impl Into<String>) -> Self {
+        AocError::NoSolution(message.into())
+    }
+}
+
+impl Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AocError::Parse {
+                line,
+                column,
+                message,
+            } => write!(f, "line {}, column {}: {}", line, column, message),
+            AocError::NoSolution(message) => write!(f, "no solution: {}", message),
+            AocError::Input(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for AocError {}
+
+impl From<InputError> for AocError {
+    fn from(error: InputError) -> Self {
+        AocError::Input(error)
+    }
+}
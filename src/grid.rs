@@ -0,0 +1,345 @@
+use crate::error::{AocError, Result};
+use crate::parse::lines;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub};
+
+/// A position on a grid, or a step between two. `x` grows to the right and
+/// `y` downwards, the same way a character map is read.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point::new(0, 0);
+    pub const NORTH: Point = Point::new(0, -1);
+    pub const EAST: Point = Point::new(1, 0);
+    pub const SOUTH: Point = Point::new(0, 1);
+    pub const WEST: Point = Point::new(-1, 0);
+
+    pub const fn new(x: i64, y: i64) -> Self {
+        Point { x, y }
+    }
+
+    /// Distance to the origin moving only along the axes.
+    pub fn manhattan(self) -> u64 {
+        self.x.unsigned_abs() + self.y.unsigned_abs()
+    }
+
+    /// A quarter turn clockwise around the origin.
+    pub fn turn_right(self) -> Self {
+        Point::new(-self.y, self.x)
+    }
+
+    /// A quarter turn counterclockwise around the origin.
+    pub fn turn_left(self) -> Self {
+        Point::new(self.y, -self.x)
+    }
+}
+
+/// Steps to the four orthogonal neighbours, clockwise from north.
+pub const ORTHOGONAL: [Point; 4] = [Point::NORTH, Point::EAST, Point::SOUTH, Point::WEST];
+
+/// Steps to all eight neighbours, clockwise from north.
+pub const ADJACENT: [Point; 8] = [
+    Point::new(0, -1),
+    Point::new(1, -1),
+    Point::new(1, 0),
+    Point::new(1, 1),
+    Point::new(0, 1),
+    Point::new(-1, 1),
+    Point::new(-1, 0),
+    Point::new(-1, -1),
+];
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, rhs: Point) -> Point {
+        Point::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, rhs: Point) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, rhs: Point) -> Point {
+        Point::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<i64> for Point {
+    type Output = Point;
+
+    fn mul(self, rhs: i64) -> Point {
+        Point::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+/// What happens to points past an edge of the grid.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Wrap {
+    /// They are outside.
+    #[default]
+    None,
+    /// The grid repeats to the left and right, like day 3's forest.
+    Horizontal,
+    /// The grid repeats up and down.
+    Vertical,
+    Both,
+}
+
+/// A dense rectangular grid, stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+    wrap: Wrap,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Grid {
+            cells: vec![fill; width * height],
+            width,
+            height,
+            wrap: Wrap::None,
+        }
+    }
+
+    /// Reads a character map, turning each character into a cell through
+    /// `legend`. Every row must be as wide as the first one.
+    pub fn parse(input: &str, legend: &[(char, T)]) -> Result<Self> {
+        let width = input.lines().next().map_or(0, |line| line.chars().count());
+        if width == 0 {
+            return Err(AocError::parse(1, 1, "expected a row of the map"));
+        }
+        let mut cells = Vec::new();
+        for line in lines(input) {
+            for (column, c) in line.text.chars().enumerate() {
+                match legend.iter().find(|(symbol, _)| *symbol == c) {
+                    Some((_, cell)) => cells.push(cell.clone()),
+                    None => {
+                        let message = format!("expected {}, found `{}`", symbols(legend), c);
+                        return Err(AocError::parse(line.number, column + 1, message));
+                    }
+                }
+            }
+            if cells.len() != line.number * width {
+                let message = format!("expected rows of {} cells", width);
+                return Err(AocError::parse(line.number, 1, message));
+            }
+        }
+        Ok(Grid {
+            height: cells.len() / width,
+            cells,
+            width,
+            wrap: Wrap::None,
+        })
+    }
+}
+
+//"`a`, `b` or `c`"
+fn symbols<T>(legend: &[(char, T)]) -> String {
+    let symbols: Vec<String> = legend.iter().map(|(c, _)| format!("`{}`", c)).collect();
+    match symbols.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        None => "nothing".to_string(),
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn with_wrap(self, wrap: Wrap) -> Self {
+        Grid { wrap, ..self }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn wrap(&self) -> Wrap {
+        self.wrap
+    }
+
+    /// Whether `point` is on the grid itself, wrapping aside.
+    pub fn contains(&self, point: Point) -> bool {
+        (0..self.width as i64).contains(&point.x) && (0..self.height as i64).contains(&point.y)
+    }
+
+    /// Where `point` lands on the grid once wrapped, if it does.
+    pub fn resolve(&self, point: Point) -> Option<Point> {
+        let (width, height) = (self.width as i64, self.height as i64);
+        let point = match self.wrap {
+            Wrap::None => point,
+            Wrap::Horizontal => Point::new(point.x.rem_euclid(width), point.y),
+            Wrap::Vertical => Point::new(point.x, point.y.rem_euclid(height)),
+            Wrap::Both => Point::new(point.x.rem_euclid(width), point.y.rem_euclid(height)),
+        };
+        Some(point).filter(|&point| self.contains(point))
+    }
+
+    fn index_of(&self, point: Point) -> Option<usize> {
+        self.resolve(point)
+            .map(|point| point.y as usize * self.width + point.x as usize)
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.index_of(point).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.index_of(point).map(move |i| &mut self.cells[i])
+    }
+
+    /// Every point on the grid, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.height as i64).flat_map(move |y| (0..width as i64).map(move |x| Point::new(x, y)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    /// The cells `offsets` away from `point` that are on the grid.
+    pub fn neighbours<'a>(
+        &'a self,
+        point: Point,
+        offsets: &'a [Point],
+    ) -> impl Iterator<Item = (Point, &'a T)> + 'a {
+        offsets.iter().filter_map(move |&offset| {
+            let neighbour = point + offset;
+            self.get(neighbour).map(|cell| (neighbour, cell))
+        })
+    }
+
+    pub fn neighbours4(&self, point: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.neighbours(point, &ORTHOGONAL)
+    }
+
+    pub fn neighbours8(&self, point: Point) -> impl Iterator<Item = (Point, &T)> {
+        self.neighbours(point, &ADJACENT)
+    }
+
+    /// The cells met walking from `from` (not included) by `step` until
+    /// leaving the grid. Never ends along an axis that wraps.
+    pub fn ray(&self, from: Point, step: Point) -> impl Iterator<Item = (Point, &T)> {
+        (1..).map_while(move |distance| {
+            let point = from + step * distance;
+            self.get(point).map(|cell| (point, cell))
+        })
+    }
+
+    /// A grid of the same size, and wrapping, with every cell mapped.
+    pub fn map<U>(&self, mut f: impl FnMut(Point, &T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.iter().map(|(point, cell)| f(point, cell)).collect(),
+            width: self.width,
+            height: self.height,
+            wrap: self.wrap,
+        }
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        self.get(point)
+            .unwrap_or_else(|| panic!("{:?} is outside the grid", point))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        self.get_mut(point)
+            .unwrap_or_else(|| panic!("{:?} is outside the grid", point))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn example() -> Grid<bool> {
+        Grid::parse("#..\n.#.\n..#\n#..", &[('.', false), ('#', true)]).unwrap()
+    }
+
+    #[test]
+    fn points() {
+        let p = Point::new(3, -4);
+        assert_eq!(p.manhattan(), 7);
+        assert_eq!(Point::EAST.turn_right(), Point::SOUTH);
+        assert_eq!(Point::EAST.turn_left(), Point::NORTH);
+        assert_eq!(p.turn_left().turn_left(), -p);
+        assert_eq!(p + Point::NORTH * 2 - Point::EAST, Point::new(2, -6));
+    }
+
+    #[test]
+    fn parse_and_index() {
+        let mut grid = example();
+        assert_eq!((grid.width(), grid.height()), (3, 4));
+        assert!(grid[Point::new(1, 1)]);
+        assert_eq!(grid.get(Point::new(3, 0)), None);
+        grid[Point::new(2, 0)] = true;
+        assert_eq!(grid.iter().filter(|(_, &tree)| tree).count(), 5);
+        let error = Grid::parse("..\n.O", &[('.', 0), ('#', 1)]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 2: expected `.` or `#`, found `O`"
+        );
+        let error = Grid::parse("..\n...", &[('.', 0)]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 1: expected rows of 2 cells"
+        );
+        assert!(Grid::parse("", &[('.', 0)]).is_err());
+    }
+
+    #[test]
+    fn neighbourhoods() {
+        let grid = example();
+        let center = Point::new(1, 1);
+        assert_eq!(grid.neighbours4(center).count(), 4);
+        assert_eq!(grid.neighbours8(center).filter(|(_, &t)| t).count(), 2);
+        assert_eq!(grid.neighbours8(Point::ORIGIN).count(), 3);
+        let ray: Vec<Point> = grid
+            .ray(Point::ORIGIN, Point::new(1, 1))
+            .map(|(p, _)| p)
+            .collect();
+        assert_eq!(ray, [Point::new(1, 1), Point::new(2, 2)]);
+    }
+
+    #[test]
+    fn wrapping() {
+        let grid = example().with_wrap(Wrap::Horizontal);
+        assert_eq!(grid.resolve(Point::new(-1, 3)), Some(Point::new(2, 3)));
+        assert_eq!(grid.resolve(Point::new(0, 4)), None);
+        let trees = grid
+            .ray(Point::ORIGIN, Point::new(3, 1))
+            .filter(|(_, &t)| t);
+        assert_eq!(trees.count(), 1);
+        let grid = grid.with_wrap(Wrap::Both);
+        assert_eq!(grid.get(Point::new(-3, -4)), Some(&true));
+    }
+}
@@ -0,0 +1,207 @@
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+/// Environment variable overriding the directory puzzle inputs are read from.
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+pub const DEFAULT_INPUT_DIR: &str = "inputs";
+
+/// Where a single puzzle input comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// The conventional `day<N>.txt` file inside the input directory.
+    Day(u8),
+    Path(PathBuf),
+    Stdin,
+}
+
+impl Source {
+    /// `-` means stdin, anything else is a path.
+    pub fn from_arg(arg: &str) -> Self {
+        match arg {
+            "-" => Source::Stdin,
+            path => Source::Path(PathBuf::from(path)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    Missing { day: Option<u8>, path: PathBuf },
+    Io { path: PathBuf, error: io::Error },
+    Stdin(io::Error),
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Missing {
+                day: Some(day),
+                path,
+            } => write!(
+                f,
+                "no input for day {}: {} does not exist (set {} or pass --input)",
+                day,
+                path.display(),
+                INPUT_DIR_VAR
+            ),
+            InputError::Missing { day: None, path } => {
+                write!(f, "input file {} does not exist", path.display())
+            }
+            InputError::Io { path, error } => write!(f, "can't read {}: {}", path.display(), error),
+            InputError::Stdin(error) => write!(f, "can't read stdin: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Resolves puzzle inputs from a directory holding `day<N>.txt` files.
+#[derive(Debug, Clone)]
+pub struct Inputs {
+    dir: PathBuf,
+}
+
+impl Inputs {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Inputs { dir: dir.into() }
+    }
+
+    /// Uses `AOC_INPUT_DIR` when set, `inputs` otherwise.
+    pub fn from_env() -> Self {
+        Inputs::new(env::var_os(INPUT_DIR_VAR).unwrap_or_else(|| DEFAULT_INPUT_DIR.into()))
+    }
+
+    /// The inputs checked into this repository, whatever the working directory.
+    pub fn bundled() -> Self {
+        Inputs::new(Path::new(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_INPUT_DIR))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn path(&self, day: u8) -> PathBuf {
+        self.dir.join(format!("day{}.txt", day))
+    }
+
+    pub fn day(&self, day: u8) -> Result<String, InputError> {
+        read_file(&self.path(day), Some(day))
+    }
+
+    /// Any other file in the input directory, like the puzzle examples.
+    pub fn file(&self, name: &str) -> Result<String, InputError> {
+        read_file(&self.dir.join(name), None)
+    }
+
+    pub fn load(&self, source: &Source) -> Result<String, InputError> {
+        match source {
+            Source::Day(day) => self.day(*day),
+            Source::Path(path) => read_file(path, None),
+            Source::Stdin => {
+                let mut input = String::new();
+                io::stdin()
+                    .read_to_string(&mut input)
+                    .map_err(InputError::Stdin)?;
+                Ok(input)
+            }
+        }
+    }
+}
+
+fn read_file(path: &Path, day: Option<u8>) -> Result<String, InputError> {
+    fs::read_to_string(path).map_err(|error| match error.kind() {
+        io::ErrorKind::NotFound => InputError::Missing {
+            day,
+            path: path.to_path_buf(),
+        },
+        _ => InputError::Io {
+            path: path.to_path_buf(),
+            error,
+        },
+    })
+}
+
+/// Puts a puzzle input in the shape every parser expects: no byte order mark,
+/// `\n` line endings, no trailing whitespace on any line, no blank lines at
+/// the end and a single final newline. Borrows when there's nothing to fix.
+pub fn normalize(raw: &str) -> Cow<'_, str> {
+    let text = raw.strip_prefix('\u{feff}').unwrap_or(raw);
+    let body = text.trim_end();
+    if body.is_empty() {
+        return Cow::Borrowed(body);
+    }
+    let clean_lines = body
+        .split('\n')
+        .all(|line| line.len() == line.trim_end().len());
+    if clean_lines && text.len() == body.len() + 1 && text.ends_with('\n') {
+        return Cow::Borrowed(text);
+    }
+    let mut normalized = String::with_capacity(body.len() + 1);
+    for line in body.split('\n') {
+        normalized.push_str(line.trim_end());
+        normalized.push('\n');
+    }
+    Cow::Owned(normalized)
+}
+
+/// Reads one of the bundled input files, for tests.
+#[cfg(test)]
+pub(crate) fn bundled(name: &str) -> String {
+    Inputs::bundled().file(name).unwrap()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn sources() {
+        assert_eq!(Source::from_arg("-"), Source::Stdin);
+        assert_eq!(
+            Source::from_arg("day1.txt"),
+            Source::Path(PathBuf::from("day1.txt"))
+        );
+    }
+
+    #[test]
+    fn bundled_days() {
+        let inputs = Inputs::bundled();
+        assert!(inputs.path(13).ends_with("inputs/day13.txt"));
+        assert!(inputs
+            .load(&Source::Day(13))
+            .unwrap()
+            .starts_with("1002462"));
+    }
+
+    #[test]
+    fn normalized() {
+        let expected = "ab\n\ncd\n";
+        for raw in [
+            "ab\n\ncd",
+            "ab\n\ncd\n",
+            "ab\r\n\r\ncd\r\n",
+            "\u{feff}ab \n\t\ncd\n\n\n",
+        ] {
+            assert_eq!(normalize(raw), expected);
+        }
+        assert!(matches!(normalize(expected), Cow::Borrowed(_)));
+        assert!(matches!(normalize("\u{feff}ab\n"), Cow::Borrowed("ab\n")));
+        assert_eq!(normalize(" \r\n\n"), "");
+    }
+
+    #[test]
+    fn missing_input() {
+        let error = Inputs::new("nowhere").day(3).unwrap_err();
+        assert!(matches!(error, InputError::Missing { day: Some(3), .. }));
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "no input for day 3: {} does not exist (set AOC_INPUT_DIR or pass --input)",
+                Path::new("nowhere").join("day3.txt").display()
+            )
+        );
+    }
+}
@@ -0,0 +1,957 @@
+use crate::grid::{Grid, Point, ADJACENT};
+use crate::Part;
+use itertools::Itertools;
+use rand::seq::{index, SliceRandom};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::collections::{HashMap, HashSet};
+use std::iter;
+
+//Same numbers for the same seed, whatever the platform or rand version
+type Random = ChaCha8Rng;
+
+/// A made up puzzle input, with the answers planted in it. An answer that
+/// can only be known by solving the puzzle is left out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Generated {
+    pub input: String,
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+impl Generated {
+    fn new(input: String, part1: impl ToString, part2: impl ToString) -> Self {
+        Generated {
+            input,
+            part1: Some(part1.to_string()),
+            part2: Some(part2.to_string()),
+        }
+    }
+
+    pub fn answer(&self, part: Part) -> Option<&str> {
+        match part {
+            Part::One => self.part1.as_deref(),
+            Part::Two => self.part2.as_deref(),
+        }
+    }
+}
+
+/// Makes up an input for `day`, the same one for the same `seed`. `size` is
+/// how many records it has: lines, passports, groups or rows of a map. Sizes
+/// a day can't hold, like more than 1020 boarding passes, are clamped.
+pub fn generate(day: u8, size: usize, seed: u64) -> Option<Generated> {
+    let rng = &mut Random::seed_from_u64(seed);
+    let generated = match day {
+        1 => expenses(rng, size),
+        2 => passwords(rng, size),
+        3 => forest(rng, size),
+        4 => passports(rng, size),
+        5 => boarding_passes(rng, size),
+        6 => customs_answers(rng, size),
+        7 => bag_rules(rng, size),
+        8 => boot_code(rng, size),
+        9 => xmas(rng, size),
+        10 => adapters(rng, size),
+        11 => seat_layout(rng, size),
+        12 => navigation(rng, size),
+        13 => bus_notes(rng, size),
+        14 => docking_program(rng, size),
+        15 => starting_numbers(rng, size),
+        16 => ticket_notes(rng, size),
+        17 => pocket_dimension(rng, size),
+        18 => homework(rng, size),
+        _ => return None,
+    };
+    Some(generated)
+}
+
+fn expenses(rng: &mut Random, size: usize) -> Generated {
+    //A pair and a triple add up to 2020, the rest are too big to take part
+    let planted = loop {
+        let a = rng.gen_range(1..1010);
+        let (x, y) = (rng.gen_range(1..674), rng.gen_range(1..674));
+        let planted = [a, 2020 - a, x, y, 2020 - x - y];
+        let pairs = planted
+            .iter()
+            .tuple_combinations()
+            .filter(|(a, b)| *a + *b == 2020);
+        let triples = planted
+            .iter()
+            .tuple_combinations()
+            .filter(|(a, b, c)| *a + *b + *c == 2020);
+        if pairs.count() == 1 && triples.count() == 1 {
+            break planted;
+        }
+    };
+    //Unless they add up to it with one or two of the planted ones
+    let taken: HashSet<i32> = planted
+        .iter()
+        .map(|p| 2020 - p)
+        .chain(
+            planted
+                .iter()
+                .tuple_combinations()
+                .map(|(p, q)| 2020 - p - q),
+        )
+        .collect();
+    let mut expenses = planted.to_vec();
+    while expenses.len() < size {
+        let expense = rng.gen_range(1011..2020);
+        if !taken.contains(&expense) {
+            expenses.push(expense);
+        }
+    }
+    expenses.shuffle(rng);
+    let [a, b, x, y, z] = planted;
+    Generated::new(lines(&expenses), a * b, x * y * z)
+}
+
+fn passwords(rng: &mut Random, size: usize) -> Generated {
+    let mut input = String::new();
+    let (mut first, mut second) = (0, 0);
+    for _ in 0..size {
+        let min = rng.gen_range(1..=5);
+        let max = rng.gen_range(min..=min + 10);
+        let letter = random_letter(rng);
+        let length = rng.gen_range(max..=max + 6);
+        //Biased towards the letter, so some passwords have the right amount
+        let password: String = (0..length)
+            .map(|_| match rng.gen_bool(0.3) {
+                true => letter,
+                false => random_letter(rng),
+            })
+            .collect();
+        let count = password.matches(letter).count();
+        first += (min..=max).contains(&count) as usize;
+        let at = |position: usize| password.as_bytes()[position - 1] == letter as u8;
+        second += (at(min) != at(max)) as usize;
+        input.push_str(&format!("{}-{} {}: {}\n", min, max, letter, password));
+    }
+    Generated::new(input, first, second)
+}
+
+fn random_letter(rng: &mut Random) -> char {
+    (b'a' + rng.gen_range(0..26)) as char
+}
+
+fn forest(rng: &mut Random, size: usize) -> Generated {
+    const WIDTH: usize = 31;
+    let mut rows: Vec<Vec<bool>> = (0..size.max(1))
+        .map(|_| (0..WIDTH).map(|_| rng.gen_bool(0.25)).collect())
+        .collect();
+    //The top left corner is always open, as in the real inputs
+    rows[0][0] = false;
+    let trees = |right: usize, down: usize| {
+        (1..)
+            .map(|step| (step * right % WIDTH, step * down))
+            .take_while(|&(_x, y)| y < rows.len())
+            .filter(|&(x, y)| rows[y][x])
+            .count()
+    };
+    let slopes = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+    let together: usize = slopes
+        .iter()
+        .map(|&(right, down)| trees(right, down))
+        .product();
+    let input = rows
+        .iter()
+        .map(|row| row.iter().map(|&tree| if tree { '#' } else { '.' }))
+        .map(|row| row.collect::<String>() + "\n")
+        .collect();
+    Generated::new(input, trees(3, 1), together)
+}
+
+const REQUIRED_FIELDS: [&str; 7] = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
+
+fn passports(rng: &mut Random, size: usize) -> Generated {
+    let (mut complete, mut valid) = (0, 0);
+    let mut input = String::new();
+    for i in 0..size {
+        let mut fields = REQUIRED_FIELDS.to_vec();
+        if rng.gen_bool(0.5) {
+            fields.push("cid");
+        }
+        //Some lack a required field, some have one that breaks its rule
+        let missing = rng.gen_bool(0.2);
+        if missing {
+            fields.remove(rng.gen_range(0..REQUIRED_FIELDS.len()));
+        }
+        let invalid = match rng.gen_bool(0.3) {
+            true => REQUIRED_FIELDS.choose(rng).copied(),
+            false => None,
+        };
+        complete += !missing as usize;
+        valid += (!missing && invalid.is_none()) as usize;
+        fields.shuffle(rng);
+        if i > 0 {
+            input.push('\n');
+        }
+        for (j, field) in fields.iter().enumerate() {
+            let value = passport_field(rng, field, Some(*field) != invalid);
+            //Spread over a few lines
+            let separator = match j {
+                0 => "",
+                _ if rng.gen_bool(0.3) => "\n",
+                _ => " ",
+            };
+            input.push_str(&format!("{}{}:{}", separator, field, value));
+        }
+        input.push('\n');
+    }
+    Generated::new(input, complete, valid)
+}
+
+fn passport_field(rng: &mut Random, field: &str, valid: bool) -> String {
+    //Invalid values never have a field name in them, which part 1 would see
+    match (field, valid) {
+        ("byr", true) => rng.gen_range(1920..=2002).to_string(),
+        ("byr", false) => rng.gen_range(1900..1920).to_string(),
+        ("iyr", true) => rng.gen_range(2010..=2020).to_string(),
+        ("iyr", false) => rng.gen_range(2000..2010).to_string(),
+        ("eyr", true) => rng.gen_range(2020..=2030).to_string(),
+        ("eyr", false) => rng.gen_range(2031..2040).to_string(),
+        ("hgt", true) if rng.gen() => format!("{}cm", rng.gen_range(150..=193)),
+        ("hgt", true) => format!("{}in", rng.gen_range(59..=76)),
+        ("hgt", false) => match rng.gen_range(0..3) {
+            0 => format!("{}cm", rng.gen_range(194..210)),
+            1 => format!("{}in", rng.gen_range(40..59)),
+            _ => rng.gen_range(150..=193).to_string(),
+        },
+        ("hcl", true) => format!("#{:06x}", rng.gen_range(0..0x1000000)),
+        ("hcl", false) => format!("{:06x}", rng.gen_range(0..0x1000000)),
+        ("ecl", true) => ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"]
+            .choose(rng)
+            .unwrap()
+            .to_string(),
+        ("ecl", false) => ["zzz", "wat", "utc", "xry"]
+            .choose(rng)
+            .unwrap()
+            .to_string(),
+        ("pid", true) => format!("{:09}", rng.gen_range(0..1_000_000_000)),
+        ("pid", false) => format!("{:010}", rng.gen_range(0..10_000_000_000_u64)),
+        _ => rng.gen_range(100..350).to_string(),
+    }
+}
+
+fn boarding_passes(rng: &mut Random, size: usize) -> Generated {
+    //A run of taken seats with yours missing in between
+    let size = size.clamp(2, 1020);
+    let first = rng.gen_range(1..=1022 - size);
+    let yours = rng.gen_range(first + 1..first + size);
+    let mut ids: Vec<usize> = (first..=first + size).filter(|&id| id != yours).collect();
+    ids.shuffle(rng);
+    let mut input = String::new();
+    for id in ids {
+        let row = (0..7)
+            .rev()
+            .map(|bit| if id >> (bit + 3) & 1 == 1 { 'B' } else { 'F' });
+        let column = (0..3)
+            .rev()
+            .map(|bit| if id >> bit & 1 == 1 { 'R' } else { 'L' });
+        input.extend(row.chain(column).chain(iter::once('\n')));
+    }
+    Generated::new(input, first + size, yours)
+}
+
+fn customs_answers(rng: &mut Random, size: usize) -> Generated {
+    //Questions are bits, so a group's answers are an or, and those in common an and
+    const QUESTIONS: u32 = (1 << 26) - 1;
+    let (mut anyone, mut everyone) = (0, 0);
+    let mut input = String::new();
+    for i in 0..size {
+        let common = rng.gen::<u32>() & rng.gen::<u32>() & rng.gen::<u32>() & QUESTIONS;
+        let people: Vec<u32> = (0..rng.gen_range(1..=5))
+            .map(
+                |_| match common | rng.gen::<u32>() & rng.gen::<u32>() & QUESTIONS {
+                    0 => 1 << rng.gen_range(0..26),
+                    answers => answers,
+                },
+            )
+            .collect();
+        anyone += people
+            .iter()
+            .fold(0, |all, answers| all | answers)
+            .count_ones();
+        everyone += people
+            .iter()
+            .fold(QUESTIONS, |all, answers| all & answers)
+            .count_ones();
+        if i > 0 {
+            input.push('\n');
+        }
+        for answers in people {
+            let mut letters: Vec<char> = (0..26)
+                .filter(|bit| answers >> bit & 1 == 1)
+                .map(|bit| (b'a' + bit as u8) as char)
+                .collect();
+            letters.shuffle(rng);
+            input.extend(letters.into_iter().chain(iter::once('\n')));
+        }
+    }
+    Generated::new(input, anyone, everyone)
+}
+
+fn bag_color(bag: usize) -> String {
+    //Words of two syllables or more, which "gold" could never be
+    const ADJECTIVES: [&str; 16] = [
+        "light", "dark", "bright", "muted", "faded", "dotted", "vibrant", "dull", "pale", "posh",
+        "plaid", "drab", "wavy", "striped", "mirrored", "clear",
+    ];
+    let syllable = |n: usize| {
+        let consonant = b"bcdfghjklmnprstvz"[n % 17] as char;
+        let vowel = b"aeiou"[n / 17 % 5] as char;
+        format!("{}{}", consonant, vowel)
+    };
+    let n = bag / ADJECTIVES.len();
+    let mut word = syllable(n % 85) + &syllable(n / 85 % 85);
+    let mut rest = n / 85 / 85;
+    while rest > 0 {
+        word += &syllable(rest % 85);
+        rest /= 85;
+    }
+    format!("{} {}", ADJECTIVES[bag % ADJECTIVES.len()], word)
+}
+
+fn bag_rules(rng: &mut Random, size: usize) -> Generated {
+    //Bags only contain some of the next few bags, so there are no cycles but
+    //there are long chains. What is inside shiny gold comes from the bags
+    //right after it, to keep the count small
+    const NEXT: usize = 40;
+    const INSIDE: usize = 8;
+    let bags = size.max(1);
+    let gold = rng.gen_range(bags / 2..bags);
+    let mut contents: Vec<Vec<(u32, usize)>> = Vec::new();
+    for bag in 0..bags {
+        let (later, fewest, most, count) = if bag >= gold && bag < gold + INSIDE {
+            (bag + 1..bags.min(gold + INSIDE), 1, 2, 3)
+        } else {
+            (
+                bag + 1..bags.min(bag + 1 + NEXT),
+                (bag < gold) as usize,
+                3,
+                4,
+            )
+        };
+        let amount = rng.gen_range(fewest.min(later.len())..=most.min(later.len()));
+        let inner = index::sample(rng, later.len(), amount)
+            .into_iter()
+            .map(|i| (rng.gen_range(1..=count), later.start + i))
+            .collect();
+        contents.push(inner);
+    }
+    let mut outer = vec![Vec::new(); bags];
+    for (bag, inner) in contents.iter().enumerate() {
+        for &(_count, inner_bag) in inner {
+            outer[inner_bag].push(bag);
+        }
+    }
+    let mut holders = HashSet::new();
+    let mut pending = vec![gold];
+    while let Some(bag) = pending.pop() {
+        pending.extend(outer[bag].iter().filter(|&&bag| holders.insert(bag)));
+    }
+    let mut total = vec![0; bags];
+    for bag in (gold..bags.min(gold + INSIDE)).rev() {
+        total[bag] = contents[bag]
+            .iter()
+            .map(|&(n, inner)| n * (1 + total[inner]))
+            .sum();
+    }
+    let color = |bag: usize| match bag {
+        _ if bag == gold => "shiny gold".to_string(),
+        _ => bag_color(bag),
+    };
+    let mut rules: Vec<String> = contents
+        .iter()
+        .enumerate()
+        .map(|(bag, inner)| {
+            let inner = match inner.is_empty() {
+                true => "no other bags".to_string(),
+                false => inner
+                    .iter()
+                    .map(|&(n, bag)| {
+                        format!("{} {} bag{}", n, color(bag), if n > 1 { "s" } else { "" })
+                    })
+                    .join(", "),
+            };
+            format!("{} bags contain {}.", color(bag), inner)
+        })
+        .collect();
+    rules.shuffle(rng);
+    Generated::new(lines(&rules), holders.len(), total[gold])
+}
+
+fn boot_code(rng: &mut Random, size: usize) -> Generated {
+    //The program runs straight to its end, jumping over dead code, but for a
+    //jmp back that loops. That's the only fix: swapping a nop on the way jumps
+    //back too, and swapping a jmp over dead code runs into a jmp back there
+    let size = size.max(1);
+    let mut program = vec![("nop", 0); size];
+    let looping = rng.gen_range(0..size);
+    let mut visited = Vec::new();
+    let (mut fixed, mut before, mut after) = (false, 0, 0);
+    let mut ip = 0;
+    while ip < size {
+        visited.push(ip);
+        let back =
+            |rng: &mut Random, from: usize| *visited.choose(rng).unwrap() as i64 - from as i64;
+        let jump = match size - ip - 1 {
+            over if over >= 2 && rng.gen_bool(0.2) => rng.gen_range(2..=over.min(6)),
+            _ => 1,
+        };
+        if !fixed && ip >= looping {
+            program[ip] = ("jmp", back(rng, ip));
+            fixed = true;
+        } else if jump > 1 {
+            program[ip] = ("jmp", jump as i64);
+            program[ip + 1] = ("jmp", back(rng, ip + 1));
+            for dead in &mut program[ip + 2..ip + jump] {
+                let operation = ["acc", "jmp", "nop"].choose(rng).unwrap();
+                *dead = (operation, rng.gen_range(-20..=20));
+            }
+            ip += jump;
+            continue;
+        } else if rng.gen_bool(0.3) {
+            program[ip] = ("nop", back(rng, ip));
+        } else {
+            let argument = rng.gen_range(-50..=50);
+            program[ip] = ("acc", argument);
+            after += argument;
+            if !fixed {
+                before += argument;
+            }
+        }
+        ip += 1;
+    }
+    let input = program
+        .iter()
+        .map(|(operation, argument)| format!("{} {:+}\n", operation, argument))
+        .collect();
+    Generated::new(input, before, after)
+}
+
+fn xmas(rng: &mut Random, size: usize) -> Generated {
+    //Numbers can only grow by adding, so they'd overflow before long. Past a
+    //limit they repeat one from the window plus a zero instead, and every
+    //window keeps two zeros around to make that, or another zero, valid
+    const PREAMBLE: usize = 25;
+    const LIMIT: u64 = 1_000_000_000_000;
+    let size = size.max(PREAMBLE + 1);
+    let mut numbers: Vec<u64> = (0..PREAMBLE).map(|_| rng.gen_range(1..=50)).collect();
+    for zero in index::sample(rng, PREAMBLE, 2) {
+        numbers[zero] = 0;
+    }
+    while numbers.len() < size - 1 {
+        let window = &numbers[numbers.len() - PREAMBLE..];
+        let next = match window[1..].iter().filter(|&&n| n == 0).count() {
+            0 | 1 => 0,
+            _ => {
+                let picked = index::sample(rng, PREAMBLE, 2);
+                let (a, b) = (window[picked.index(0)], window[picked.index(1)]);
+                if a + b <= LIMIT {
+                    a + b
+                } else {
+                    a
+                }
+            }
+        };
+        numbers.push(next);
+    }
+    //The invalid number goes last, adding up the only run that makes it
+    let window = &numbers[numbers.len() - PREAMBLE..];
+    let (invalid, weakness) = loop {
+        let length = rng.gen_range(2..=numbers.len().min(17));
+        let start = rng.gen_range(0..=numbers.len() - length);
+        let run = &numbers[start..start + length];
+        let invalid: u64 = run.iter().sum();
+        if !numbers.contains(&invalid)
+            && !window
+                .iter()
+                .tuple_combinations()
+                .any(|(a, b)| a + b == invalid)
+            && runs_adding_up(&numbers, invalid) == 1
+        {
+            let weakness = run.iter().min().unwrap() + run.iter().max().unwrap();
+            break (invalid, weakness);
+        }
+    };
+    numbers.push(invalid);
+    Generated::new(lines(&numbers), invalid, weakness)
+}
+
+//Runs of two numbers or more adding up to `target`
+fn runs_adding_up(numbers: &[u64], target: u64) -> usize {
+    let sums: Vec<u64> = iter::once(0)
+        .chain(numbers.iter().scan(0, |sum, n| {
+            *sum += n;
+            Some(*sum)
+        }))
+        .collect();
+    let mut seen = HashMap::new();
+    let mut runs = 0;
+    for end in 2..sums.len() {
+        *seen.entry(sums[end - 2]).or_insert(0) += 1;
+        if let Some(start) = sums[end].checked_sub(target) {
+            runs += seen.get(&start).unwrap_or(&0);
+        }
+    }
+    runs
+}
+
+fn adapters(rng: &mut Random, size: usize) -> Generated {
+    //Steps of 1 or 3 jolts, starting at 1 above the outlet. Every step of 1
+    //makes more arrangements, so past a limit the steps are all 3
+    const LIMIT: usize = 1 << 50;
+    let mut joltages = vec![0];
+    let mut arrangements = vec![1];
+    let (mut ones, mut threes) = (0, 1);
+    for i in 0..size.max(1) {
+        let last = *joltages.last().unwrap();
+        let reaching = |joltage: usize| -> usize {
+            let reachable = joltages.iter().zip(&arrangements).rev().take(3);
+            reachable
+                .filter(|(&from, _)| from + 3 >= joltage)
+                .map(|(_, n)| n)
+                .sum()
+        };
+        let step = match i == 0 || rng.gen_bool(0.6) {
+            true if reaching(last + 1) <= LIMIT => 1,
+            _ => 3,
+        };
+        ones += (step == 1) as usize;
+        threes += (step == 3) as usize;
+        arrangements.push(reaching(last + step));
+        joltages.push(last + step);
+    }
+    let mut adapters = joltages.split_off(1);
+    adapters.shuffle(rng);
+    Generated::new(
+        lines(&adapters),
+        ones * threes,
+        arrangements.last().unwrap(),
+    )
+}
+
+fn seat_layout(rng: &mut Random, size: usize) -> Generated {
+    //No seat has more than 3 others next to it or sees more than 4, so all of
+    //them get taken on the first round and nobody ever leaves
+    const WIDTH: usize = 90;
+    let mut seats = Grid::new(WIDTH, size.max(1), false);
+    let mut adjacent = Grid::new(WIDTH, size.max(1), 0);
+    let mut visible = Grid::new(WIDTH, size.max(1), 0);
+    let mut taken = 0;
+    for point in seats.points().collect::<Vec<_>>() {
+        if !rng.gen_bool(0.7) {
+            continue;
+        }
+        let near: Vec<Point> = seats
+            .neighbours8(point)
+            .filter(|(_, &seat)| seat)
+            .map(|(near, _)| near)
+            .collect();
+        let seen: Vec<Option<Point>> = ADJACENT
+            .iter()
+            .map(|&step| {
+                seats
+                    .ray(point, step)
+                    .find(|(_, &seat)| seat)
+                    .map(|(p, _)| p)
+            })
+            .collect();
+        //Seats that saw each other across this one now see it instead, and
+        //those that saw nothing past it see one more
+        let gaining: Vec<Point> = (0..ADJACENT.len())
+            .filter_map(|i| match (seen[i], seen[(i + 4) % ADJACENT.len()]) {
+                (Some(seat), None) => Some(seat),
+                _ => None,
+            })
+            .collect();
+        let sees = seen.iter().flatten().count();
+        if near.len() > 3
+            || near.iter().any(|&seat| adjacent[seat] == 3)
+            || sees > 4
+            || gaining.iter().any(|&seat| visible[seat] == 4)
+        {
+            continue;
+        }
+        seats[point] = true;
+        adjacent[point] = near.len();
+        near.iter().for_each(|&seat| adjacent[seat] += 1);
+        visible[point] = sees;
+        gaining.iter().for_each(|&seat| visible[seat] += 1);
+        taken += 1;
+    }
+    let mut input = String::new();
+    for y in 0..seats.height() as i64 {
+        let row = (0..WIDTH as i64).map(|x| if seats[Point::new(x, y)] { 'L' } else { '.' });
+        input.extend(row.chain(iter::once('\n')));
+    }
+    Generated::new(input, taken, taken)
+}
+
+fn navigation(rng: &mut Random, size: usize) -> Generated {
+    //Both ways of reading the instructions, followed as they're made
+    let (mut ship, mut heading) = (Point::ORIGIN, Point::EAST);
+    let (mut waypoint_ship, mut waypoint) = (Point::ORIGIN, Point::new(10, -1));
+    let mut input = String::new();
+    for _ in 0..size {
+        let action = *b"NSEWLRF".choose(rng).unwrap() as char;
+        let value = match action {
+            'L' | 'R' => 90 * rng.gen_range(1..=3),
+            _ => rng.gen_range(1..=100),
+        };
+        match action {
+            'L' | 'R' => {
+                for _ in 0..value / 90 {
+                    let turn = if action == 'L' {
+                        Point::turn_left
+                    } else {
+                        Point::turn_right
+                    };
+                    heading = turn(heading);
+                    waypoint = turn(waypoint);
+                }
+            }
+            'F' => {
+                ship += heading * value;
+                waypoint_ship += waypoint * value;
+            }
+            _ => {
+                let direction = match action {
+                    'N' => Point::NORTH,
+                    'S' => Point::SOUTH,
+                    'E' => Point::EAST,
+                    _ => Point::WEST,
+                };
+                ship += direction * value;
+                waypoint += direction * value;
+            }
+        }
+        input.push_str(&format!("{}{}\n", action, value));
+    }
+    Generated::new(input, ship.manhattan(), waypoint_ship.manhattan())
+}
+
+fn bus_notes(rng: &mut Random, size: usize) -> Generated {
+    //Ids are primes, so the timestamp follows from the chinese remainder theorem
+    let slots = size.max(1);
+    let mut primes: Vec<u64> = (2..1000_u64)
+        .filter(|&n| (2..n).take_while(|d| d * d <= n).all(|d| n % d != 0))
+        .collect();
+    primes.shuffle(rng);
+    let mut buses = Vec::new();
+    let mut product: u64 = 1;
+    for (prime, slot) in primes
+        .into_iter()
+        .zip(index::sample(rng, slots, slots.min(9)))
+    {
+        if product.saturating_mul(prime) >= 1 << 50 {
+            break;
+        }
+        product *= prime;
+        buses.push((slot as u64, prime));
+    }
+    let timestamp = buses.iter().fold(0, |timestamp, &(slot, prime)| {
+        let others = product / prime;
+        //Fermat's little theorem gives the inverse modulo a prime
+        let inverse = power(others % prime, prime - 2, prime);
+        let remainder = (prime - slot % prime) % prime;
+        let term = remainder as u128 * others as u128 * inverse as u128;
+        ((timestamp as u128 + term) % product as u128) as u64
+    });
+    //No bus leaves right at the earliest time, and no two leave at once after it
+    let (earliest, first_bus) = loop {
+        let earliest = rng.gen_range(1_000..1_000_000);
+        let waits: Vec<(u64, u64)> = buses
+            .iter()
+            .map(|&(_, id)| (id - earliest % id, id))
+            .sorted()
+            .collect();
+        let unique = waits.get(1).is_none_or(|second| second.0 != waits[0].0);
+        if unique && buses.iter().all(|&(_, id)| earliest % id != 0) {
+            break (earliest, waits[0].0 * waits[0].1);
+        }
+    };
+    let mut ids = vec!["x".to_string(); slots];
+    for &(slot, id) in &buses {
+        ids[slot as usize] = id.to_string();
+    }
+    let input = format!("{}\n{}\n", earliest, ids.join(","));
+    Generated::new(input, first_bus, timestamp)
+}
+
+fn power(base: u64, exponent: u64, modulus: u64) -> u64 {
+    (0..64).rev().fold(1, |result: u64, bit| {
+        let squared = result * result % modulus;
+        match exponent >> bit & 1 {
+            1 => squared * base % modulus,
+            _ => squared,
+        }
+    })
+}
+
+fn docking_program(rng: &mut Random, size: usize) -> Generated {
+    //Up to 9 floating bits, like the puzzle, so every write stays cheap
+    let mut memory = HashMap::new();
+    let mut decoded = HashMap::new();
+    let (mut ones, mut zeros, mut floating) = (0_u64, 0_u64, 0_u64);
+    let mut input = String::new();
+    for i in 0..size {
+        if i == 0 || rng.gen_bool(0.25) {
+            let amount = rng.gen_range(0..=9);
+            let floating_bits = index::sample(rng, 36, amount).into_vec();
+            let mask: String = (0..36)
+                .map(|bit| match bit {
+                    _ if floating_bits.contains(&bit) => 'X',
+                    _ if rng.gen() => '1',
+                    _ => '0',
+                })
+                .collect();
+            let bits = |c: char| mask.chars().fold(0, |bits, m| bits << 1 | (m == c) as u64);
+            ones = bits('1');
+            zeros = bits('0');
+            floating = bits('X');
+            input.push_str(&format!("mask = {}\n", mask));
+            continue;
+        }
+        let address = rng.gen_range(0..1 << 16);
+        let value = rng.gen_range(0..1 << 30);
+        memory.insert(address, (value | ones) & !zeros);
+        //Every combination of the floating bits, from all set to none
+        let base = (address | ones) & !floating;
+        let mut set = floating;
+        loop {
+            decoded.insert(base | set, value);
+            if set == 0 {
+                break;
+            }
+            set = (set - 1) & floating;
+        }
+        input.push_str(&format!("mem[{}] = {}\n", address, value));
+    }
+    Generated::new(
+        input,
+        memory.values().sum::<u64>(),
+        decoded.values().sum::<u64>(),
+    )
+}
+
+fn starting_numbers(rng: &mut Random, size: usize) -> Generated {
+    //The 2020th number is cheap to know, the 30000000th one takes playing
+    let size = size.max(1);
+    let numbers: Vec<usize> = index::sample(rng, (size * 2).max(20), size).into_vec();
+    let spoken = match numbers.get(2020 - 1) {
+        Some(&spoken) => spoken,
+        None => {
+            let mut last_turn: HashMap<usize, usize> = numbers
+                .iter()
+                .enumerate()
+                .map(|(turn, &n)| (n, turn + 1))
+                .collect();
+            let mut spoken = *numbers.last().unwrap();
+            last_turn.remove(&spoken);
+            for turn in numbers.len()..2020 {
+                let next = last_turn.get(&spoken).map_or(0, |&last| turn - last);
+                last_turn.insert(spoken, turn);
+                spoken = next;
+            }
+            spoken
+        }
+    };
+    Generated {
+        input: format!("{}\n", numbers.iter().join(",")),
+        part1: Some(spoken.to_string()),
+        part2: None,
+    }
+}
+
+const TICKET_FIELDS: [&str; 20] = [
+    "departure location",
+    "departure station",
+    "departure platform",
+    "departure track",
+    "departure date",
+    "departure time",
+    "arrival location",
+    "arrival station",
+    "arrival platform",
+    "arrival track",
+    "class",
+    "duration",
+    "price",
+    "route",
+    "row",
+    "seat",
+    "train",
+    "type",
+    "wagon",
+    "zone",
+];
+
+fn ticket_notes(rng: &mut Random, size: usize) -> Generated {
+    //Each field's ranges hold those of a lower level and a few values more,
+    //and nothing is valid from 501 to 599. One ticket has, in each column,
+    //a value only fields of that column's level or above take, so a column
+    //fits just as many fields as levels above its own, one for the highest
+    let fields = TICKET_FIELDS.len();
+    let mut levels: Vec<usize> = (0..fields).collect();
+    levels.shuffle(rng);
+    let ranges = |level: usize| (195 - 5 * level, 500, 600, 805 + 5 * level);
+    let mut columns: Vec<usize> = (0..fields).collect();
+    columns.shuffle(rng);
+    let valid = |rng: &mut Random, column: usize| {
+        let (low, _, _, high) = ranges(levels[columns[column]]);
+        match rng.gen() {
+            true => rng.gen_range(low..=500),
+            false => rng.gen_range(600..=high),
+        }
+    };
+    let mut input = String::new();
+    for (field, &name) in TICKET_FIELDS.iter().enumerate() {
+        let (a, b, c, d) = ranges(levels[field]);
+        input.push_str(&format!("{}: {}-{} or {}-{}\n", name, a, b, c, d));
+    }
+    let yours: Vec<usize> = (0..fields).map(|column| valid(rng, column)).collect();
+    let departures: usize = (0..fields)
+        .filter(|&column| TICKET_FIELDS[columns[column]].starts_with("departure"))
+        .map(|column| yours[column])
+        .product();
+    input.push_str(&format!(
+        "\nyour ticket:\n{}\n\nnearby tickets:\n",
+        yours.iter().join(",")
+    ));
+    let telling = rng.gen_range(0..size.max(1));
+    let mut error_rate = 0;
+    for ticket in 0..size.max(1) {
+        let mut values: Vec<usize> = (0..fields).map(|column| valid(rng, column)).collect();
+        if ticket == telling {
+            for (column, value) in values.iter_mut().enumerate() {
+                let (low, _, _, _) = ranges(levels[columns[column]]);
+                *value = rng.gen_range(low..low + 5);
+            }
+        } else if rng.gen_bool(0.25) {
+            let invalid = [
+                rng.gen_range(1..100),
+                rng.gen_range(501..600),
+                rng.gen_range(905..1000),
+            ];
+            let invalid = *invalid.choose(rng).unwrap();
+            values[rng.gen_range(0..fields)] = invalid;
+            error_rate += invalid;
+        }
+        input.push_str(&format!("{}\n", values.iter().join(",")));
+    }
+    Generated::new(input, error_rate, departures)
+}
+
+fn pocket_dimension(rng: &mut Random, size: usize) -> Generated {
+    //Squares of 2x2 cubes, far enough apart, never change in three dimensions
+    //or four: cubes in one have 3 active neighbours, cubes around it 1, 2 or 4
+    let side = size.max(2);
+    let mut cubes = vec![vec!['.'; side]; side];
+    let mut squares = 0;
+    for y in (0..side - 1).step_by(4) {
+        for x in (0..side - 1).step_by(4) {
+            if rng.gen_bool(0.5) {
+                for (dx, dy) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+                    cubes[y + dy][x + dx] = '#';
+                }
+                squares += 1;
+            }
+        }
+    }
+    let input = cubes
+        .into_iter()
+        .map(|row| row.into_iter().chain(iter::once('\n')).collect::<String>())
+        .collect();
+    Generated::new(input, 4 * squares, 4 * squares)
+}
+
+fn homework(rng: &mut Random, size: usize) -> Generated {
+    //Small enough that the sum of every result fits
+    const LIMIT: u64 = 1_000_000_000_000;
+    let (mut left_to_right, mut additions_first) = (0, 0);
+    let mut input = String::new();
+    for _ in 0..size {
+        let (text, first, second) = iter::repeat_with(|| expression(rng, 0))
+            .flatten()
+            .find(|&(_, first, second)| first <= LIMIT && second <= LIMIT)
+            .unwrap();
+        left_to_right += first;
+        additions_first += second;
+        input.push_str(&text);
+        input.push('\n');
+    }
+    Generated::new(input, left_to_right, additions_first)
+}
+
+//An expression with its value evaluated left to right, and with additions
+//first. None if either overflows
+fn expression(rng: &mut Random, depth: usize) -> Option<(String, u64, u64)> {
+    let mut text = String::new();
+    let (mut left_to_right, mut sum, mut product) = (0_u64, 0_u64, 1_u64);
+    for term in 0..rng.gen_range(2..=5) {
+        let (term_text, first, second) = match depth < 2 && rng.gen_bool(0.25) {
+            true => {
+                let (inner, first, second) = expression(rng, depth + 1)?;
+                (format!("({})", inner), first, second)
+            }
+            false => {
+                let digit = rng.gen_range(1..=9);
+                (digit.to_string(), digit, digit)
+            }
+        };
+        if term == 0 {
+            left_to_right = first;
+            sum = second;
+        } else if rng.gen() {
+            text.push_str(" + ");
+            left_to_right = left_to_right.checked_add(first)?;
+            sum = sum.checked_add(second)?;
+        } else {
+            text.push_str(" * ");
+            left_to_right = left_to_right.checked_mul(first)?;
+            product = product.checked_mul(sum)?;
+            sum = second;
+        }
+        text.push_str(&term_text);
+    }
+    Some((text, left_to_right, product.checked_mul(sum)?))
+}
+
+fn lines<T: ToString>(items: &[T]) -> String {
+    items.iter().map(|item| item.to_string() + "\n").collect()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::solution;
+
+    #[test]
+    fn planted_answers() {
+        for day in 1..=18 {
+            //Grids grow in both directions with the size of day 17
+            let size = if day == 17 { 12 } else { 60 };
+            for seed in 0..3 {
+                let generated = generate(day, size, seed).unwrap();
+                let runner = solution(day).unwrap();
+                for part in Part::ALL {
+                    if let Some(expected) = generated.answer(part) {
+                        let answer = runner.run(part, &generated.input).unwrap();
+                        assert_eq!(answer, expected, "day {} {:?} seed {}", day, part, seed);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn seeded() {
+        assert_eq!(generate(9, 100, 42), generate(9, 100, 42));
+        assert_ne!(generate(9, 100, 42), generate(9, 100, 43));
+        assert_eq!(generate(4, 10, 1).unwrap().input.split("\n\n").count(), 10);
+        assert_eq!(generate(5, 5000, 0).unwrap().input.lines().count(), 1020);
+        assert!(generate(19, 10, 0).is_none());
+    }
+}
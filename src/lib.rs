@@ -16,12 +16,85 @@ pub mod day6;
 pub mod day7;
 pub mod day8;
 pub mod day9;
-//pub mod main;
+pub mod error;
+pub mod grid;
+pub mod input;
+pub mod inputgen;
+pub mod manifest;
+pub mod memory;
+pub mod parse;
+pub mod report;
+pub mod schema;
+pub mod solution;
+
+pub use error::{AocError, Result};
+pub use solution::{Driver, Part, Runner, Solution};
+
+/// Every implemented day, in order.
+static SOLUTIONS: [&dyn Runner; 18] = [
+    &day1::Day1,
+    &day2::Day2,
+    &day3::Day3,
+    &day4::Day4,
+    &day5::Day5,
+    &day6::Day6,
+    &day7::Day7,
+    &day8::Day8,
+    &day9::Day9,
+    &day10::Day10,
+    &day11::Day11,
+    &day12::Day12,
+    &day13::Day13,
+    &day14::Day14,
+    &day15::Day15,
+    &day16::Day16,
+    &day17::Day17,
+    &day18::Day18,
+];
+
+pub fn solutions() -> &'static [&'static dyn Runner] {
+    &SOLUTIONS
+}
+
+pub fn solution(day: u8) -> Option<&'static dyn Runner> {
+    SOLUTIONS.iter().copied().find(|s| s.day() == day)
+}
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::input::Inputs;
+    use crate::manifest::{Manifest, MANIFEST_FILE};
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn registry_in_order() {
+        let days: Vec<u8> = solutions().iter().map(|s| s.day()).collect();
+        assert_eq!(days, (1..=18).collect::<Vec<u8>>());
+        assert!(solution(13).is_some());
+        assert!(solution(25).is_none());
+    }
+
+    #[test]
+    fn windows_inputs() {
+        //Every day, saved on Windows with a BOM and some stray whitespace
+        let inputs = Inputs::bundled();
+        let manifest = Manifest::load(&inputs.dir().join(MANIFEST_FILE)).unwrap();
+        for entry in &manifest.answers {
+            let raw = inputs.file(&entry.input()).unwrap();
+            let input = format!("\u{feff}{} \r\n\r\n", raw.trim_end().replace('\n', "\r\n"));
+            let runner = solution(entry.day).unwrap();
+            for &part in &Part::ALL {
+                let expected = entry.expected(part).unwrap().to_string();
+                let answer = runner.run(part, &input).unwrap_or_else(|e| {
+                    panic!("day {} part {}: {}", entry.day, part, e);
+                });
+                assert_eq!(answer, expected, "day {} part {}", entry.day, part);
+            }
+        }
+    }
 }
@@ -0,0 +1,568 @@
+use aoc2020::day2;
+use aoc2020::day4;
+use aoc2020::input::{Inputs, Source};
+use aoc2020::inputgen;
+use aoc2020::manifest::{Check, Manifest, Status, MANIFEST_FILE};
+use aoc2020::memory::CountingAllocator;
+use aoc2020::report::{self, Record, Task};
+use aoc2020::schema::Schema;
+use aoc2020::{solution, solutions, Part};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use std::{env, process, thread};
+
+//Lets runs report how much memory each day allocates
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const USAGE: &str = "Usage:
+    aoc2020 run --day <N> [--part <1|2>] [--input <FILE>] [--input-dir <DIR>] [--format <FORMAT>]
+                [--jobs <N>]
+    aoc2020 run --all [--part <1|2>] [--input-dir <DIR>] [--format <FORMAT>] [--jobs <N>]
+    aoc2020 verify [--manifest <FILE>] [--input-dir <DIR>]
+    aoc2020 gen --day <N> [--size <N>] [--seed <N>]
+    aoc2020 passwords [--input <FILE>] [--input-dir <DIR>] [--policy <SPEC>] [--format <FORMAT>]
+    aoc2020 passports [--input <FILE>] [--input-dir <DIR>] [--schema <FILE>] [--format <FORMAT>]
+
+Options:
+    --day <N>          Day to run, from 1 to 25
+    --part <1|2>       Run only one part, both parts by default
+    --input <FILE>     Puzzle input file, - reads it from stdin
+    --input-dir <DIR>  Directory with day<N>.txt inputs, $AOC_INPUT_DIR or inputs by default
+    --all              Run every implemented day in order
+    --format <FORMAT>  table, json or csv, table by default. json and csv include
+                       parse and solve times in nanoseconds and heap usage
+    --jobs <N>         Days and parts to solve at once, one per CPU by default.
+                       Output keeps the usual order whatever finishes first
+    --manifest <FILE>  Expected answers, answers.toml in the input directory by default
+    --size <N>         Lines, passports, groups or rows to generate, 1000 by default
+    --seed <N>         Seed for the generated input, 0 by default. The input goes
+                       to stdout and the answers planted in it to stderr
+    --policy <SPEC>    Password policy to check each day 2 line against, count
+                       (part 1) by default. See day2::parse_policy for the rules
+    --schema <FILE>    TOML or JSON schema to check each day 4 passport against,
+                       the built-in passport schema by default";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    #[default]
+    Table,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("invalid format: {}", s)),
+        }
+    }
+}
+
+#[derive(Default)]
+struct RunOptions {
+    day: Option<u8>,
+    part: Option<Part>,
+    input: Option<Source>,
+    input_dir: Option<PathBuf>,
+    all: bool,
+    format: Format,
+    jobs: Option<usize>,
+}
+
+fn parse_run_options(mut args: impl Iterator<Item = String>) -> Result<RunOptions, String> {
+    let mut options = RunOptions::default();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+        match arg.as_str() {
+            "--day" => {
+                let day = value()?;
+                options.day = Some(day.parse().map_err(|_| format!("invalid day: {}", day))?);
+            }
+            "--part" => {
+                let part = value()?;
+                options.part = Some(
+                    part.parse()
+                        .ok()
+                        .and_then(Part::from_number)
+                        .ok_or(format!("invalid part: {}", part))?,
+                );
+            }
+            "--input" => options.input = Some(Source::from_arg(&value()?)),
+            "--input-dir" => options.input_dir = Some(PathBuf::from(value()?)),
+            "--all" => options.all = true,
+            "--format" => options.format = value()?.parse()?,
+            "--jobs" => {
+                let jobs = value()?;
+                options.jobs = Some(
+                    jobs.parse()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or(format!("invalid number of jobs: {}", jobs))?,
+                );
+            }
+            _ => return Err(format!("unexpected argument: {}", arg)),
+        }
+    }
+    match (options.all, options.day) {
+        (true, Some(_)) => Err("--all and --day can't be used together".to_string()),
+        (true, None) if options.input.is_some() => Err("--input needs a single --day".to_string()),
+        (false, None) => Err("either --day or --all is required".to_string()),
+        _ => Ok(options),
+    }
+}
+
+#[derive(Default)]
+struct VerifyOptions {
+    manifest: Option<PathBuf>,
+    input_dir: Option<PathBuf>,
+}
+
+fn parse_verify_options(mut args: impl Iterator<Item = String>) -> Result<VerifyOptions, String> {
+    let mut options = VerifyOptions::default();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+        match arg.as_str() {
+            "--manifest" => options.manifest = Some(PathBuf::from(value()?)),
+            "--input-dir" => options.input_dir = Some(PathBuf::from(value()?)),
+            _ => return Err(format!("unexpected argument: {}", arg)),
+        }
+    }
+    Ok(options)
+}
+
+struct GenOptions {
+    day: u8,
+    size: usize,
+    seed: u64,
+}
+
+fn parse_gen_options(mut args: impl Iterator<Item = String>) -> Result<GenOptions, String> {
+    let (mut day, mut size, mut seed) = (None, 1000, 0);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+        match arg.as_str() {
+            "--day" => {
+                let value = value()?;
+                day = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid day: {}", value))?,
+                );
+            }
+            "--size" => {
+                let value = value()?;
+                size = value
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or(format!("invalid size: {}", value))?;
+            }
+            "--seed" => {
+                let value = value()?;
+                seed = value
+                    .parse()
+                    .map_err(|_| format!("invalid seed: {}", value))?;
+            }
+            _ => return Err(format!("unexpected argument: {}", arg)),
+        }
+    }
+    let day = day.ok_or("--day is required")?;
+    Ok(GenOptions { day, size, seed })
+}
+
+fn print_record(record: &Record) {
+    println!(
+        "Day {:>2} part {}: {:<20} ({})",
+        record.day,
+        record.part,
+        record.answer,
+        format_duration(record.parse_time() + record.solve_time())
+    );
+}
+
+fn format_duration(elapsed: Duration) -> String {
+    let micros = elapsed.as_micros();
+    if micros < 1_000 {
+        format!("{}µs", micros)
+    } else if micros < 1_000_000 {
+        format!("{:.3}ms", micros as f64 / 1e3)
+    } else {
+        format!("{:.3}s", micros as f64 / 1e6)
+    }
+}
+
+fn run(options: RunOptions) -> Result<(), String> {
+    let inputs = match options.input_dir {
+        Some(dir) => Inputs::new(dir),
+        None => Inputs::from_env(),
+    };
+    let parts = match options.part {
+        Some(part) => vec![part],
+        None => Part::ALL.to_vec(),
+    };
+    let runners = match options.day {
+        Some(day) => vec![solution(day).ok_or(format!("day {} is not implemented", day))?],
+        None => solutions().to_vec(),
+    };
+    let jobs = options
+        .jobs
+        .or_else(|| thread::available_parallelism().ok().map(usize::from))
+        .unwrap_or(1);
+    let mut loaded = Vec::new();
+    for runner in runners {
+        let source = options.input.clone().unwrap_or(Source::Day(runner.day()));
+        let input = inputs.load(&source).map_err(|e| e.to_string())?;
+        loaded.push((runner, input));
+    }
+    //On a single thread both parts share the parsing, otherwise they run apart
+    let tasks: Vec<Task> = loaded
+        .iter()
+        .flat_map(|(runner, input)| {
+            let split = if jobs > 1 {
+                parts.iter().map(|&part| vec![part]).collect()
+            } else {
+                vec![parts.clone()]
+            };
+            split.into_iter().map(move |parts| Task {
+                runner: *runner,
+                input,
+                parts,
+            })
+        })
+        .collect();
+    let format = options.format;
+    let mut records = Vec::new();
+    let mut error = None;
+    report::measure_all(&tasks, jobs, |task, result| match result {
+        _ if error.is_some() => {}
+        Ok(task_records) => {
+            if format == Format::Table {
+                task_records.iter().for_each(print_record);
+            }
+            records.extend(task_records);
+        }
+        Err(e) => error = Some(format!("day {}: {}", task.runner.day(), e)),
+    });
+    if let Some(error) = error {
+        return Err(error);
+    }
+    match format {
+        Format::Table => {}
+        Format::Json => println!("{}", report::to_json(&records)),
+        Format::Csv => print!("{}", report::to_csv(&records)),
+    }
+    Ok(())
+}
+
+fn verify(options: VerifyOptions) -> Result<(), String> {
+    let inputs = match options.input_dir {
+        Some(dir) => Inputs::new(dir),
+        None => Inputs::from_env(),
+    };
+    let path = options
+        .manifest
+        .unwrap_or_else(|| inputs.dir().join(MANIFEST_FILE));
+    let manifest = Manifest::load(&path).map_err(|e| e.to_string())?;
+    let checks = manifest.verify(&inputs);
+    print_checks(&checks);
+    let count = |f: fn(&Status) -> bool| checks.iter().filter(|c| f(&c.status)).count();
+    let failed = count(Status::is_failure);
+    println!(
+        "\n{} passed, {} failed, {} missing",
+        count(|s| *s == Status::Pass),
+        failed,
+        count(|s| *s == Status::Missing)
+    );
+    match failed {
+        0 => Ok(()),
+        _ => Err(format!("{} of {} checks failed", failed, checks.len())),
+    }
+}
+
+fn print_checks(checks: &[Check]) {
+    let width = |column: &dyn Fn(&Check) -> usize, header: &str| {
+        checks
+            .iter()
+            .map(column)
+            .chain(Some(header.len()))
+            .max()
+            .unwrap_or(0)
+    };
+    let input = width(&|c| c.input.len(), "Input");
+    let expected = width(&|c| c.expected.as_ref().map_or(1, String::len), "Expected");
+    let actual = width(
+        &|c| match &c.status {
+            Status::Fail(actual) => actual.len(),
+            _ => c.expected.as_ref().map_or(1, String::len),
+        },
+        "Actual",
+    );
+    println!(
+        "Day  Part  {:<input$}  {:<expected$}  {:<actual$}  Status",
+        "Input",
+        "Expected",
+        "Actual",
+        input = input,
+        expected = expected,
+        actual = actual
+    );
+    for check in checks {
+        let expected_answer = check.expected.as_deref().unwrap_or("-");
+        let (actual_answer, detail) = match &check.status {
+            Status::Pass => (expected_answer, ""),
+            Status::Fail(actual) => (actual.as_str(), ""),
+            Status::Missing => ("-", ""),
+            Status::Error(error) => ("-", error.as_str()),
+        };
+        let row = format!(
+            "{:>3}  {:>4}  {:<input$}  {:<expected$}  {:<actual$}  {:<7}  {}",
+            check.day,
+            check.part.number(),
+            check.input,
+            expected_answer,
+            actual_answer,
+            check.status,
+            detail,
+            input = input,
+            expected = expected,
+            actual = actual
+        );
+        println!("{}", row.trim_end());
+    }
+}
+
+#[derive(Default)]
+struct PasswordsOptions {
+    input: Option<Source>,
+    input_dir: Option<PathBuf>,
+    policy: Option<String>,
+    format: Format,
+}
+
+fn parse_passwords_options(
+    mut args: impl Iterator<Item = String>,
+) -> Result<PasswordsOptions, String> {
+    let mut options = PasswordsOptions::default();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+        match arg.as_str() {
+            "--input" => options.input = Some(Source::from_arg(&value()?)),
+            "--input-dir" => options.input_dir = Some(PathBuf::from(value()?)),
+            "--policy" => options.policy = Some(value()?),
+            "--format" => match value()?.parse()? {
+                Format::Csv => return Err("passwords can't be reported as csv".to_string()),
+                format => options.format = format,
+            },
+            _ => return Err(format!("unexpected argument: {}", arg)),
+        }
+    }
+    Ok(options)
+}
+
+fn passwords(options: PasswordsOptions) -> Result<(), String> {
+    let inputs = match options.input_dir {
+        Some(dir) => Inputs::new(dir),
+        None => Inputs::from_env(),
+    };
+    let source = options.input.unwrap_or(Source::Day(2));
+    let input = inputs.load(&source).map_err(|e| e.to_string())?;
+    let spec = options.policy.as_deref().unwrap_or("count");
+    let policy = day2::parse_policy(spec).map_err(|e| format!("policy: {}", e))?;
+    let verdicts = day2::validate(&input, &policy).map_err(|e| format!("day 2: {}", e))?;
+    match options.format {
+        Format::Json => println!("{}", day2::verdicts_to_json(&verdicts)),
+        _ => {
+            print!("{}", day2::verdicts_to_table(&verdicts));
+            let valid = verdicts.iter().filter(|v| v.valid).count();
+            println!("\n{} valid, {} invalid", valid, verdicts.len() - valid);
+        }
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct PassportsOptions {
+    input: Option<Source>,
+    input_dir: Option<PathBuf>,
+    schema: Option<PathBuf>,
+    format: Format,
+}
+
+fn parse_passports_options(
+    mut args: impl Iterator<Item = String>,
+) -> Result<PassportsOptions, String> {
+    let mut options = PassportsOptions::default();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+        match arg.as_str() {
+            "--input" => options.input = Some(Source::from_arg(&value()?)),
+            "--input-dir" => options.input_dir = Some(PathBuf::from(value()?)),
+            "--schema" => options.schema = Some(PathBuf::from(value()?)),
+            "--format" => match value()?.parse()? {
+                Format::Csv => return Err("passports can't be reported as csv".to_string()),
+                format => options.format = format,
+            },
+            _ => return Err(format!("unexpected argument: {}", arg)),
+        }
+    }
+    Ok(options)
+}
+
+fn passports(options: PassportsOptions) -> Result<(), String> {
+    let inputs = match options.input_dir {
+        Some(dir) => Inputs::new(dir),
+        None => Inputs::from_env(),
+    };
+    let source = options.input.unwrap_or(Source::Day(4));
+    let input = inputs.load(&source).map_err(|e| e.to_string())?;
+    let loaded;
+    let schema = match options.schema {
+        Some(path) => {
+            loaded = Schema::load(&path).map_err(|e| e.to_string())?;
+            &loaded
+        }
+        None => day4::passport_schema(),
+    };
+    let reports = day4::diagnose(&input, schema).map_err(|e| format!("day 4: {}", e))?;
+    let summary = day4::summarize(&reports);
+    match options.format {
+        Format::Json => println!("{}", day4::reports_to_json(&reports, &summary)),
+        _ => {
+            print!("{}", day4::reports_to_table(&reports, &summary));
+            let valid = reports.iter().filter(|r| r.valid).count();
+            println!("\n{} valid, {} invalid", valid, reports.len() - valid);
+        }
+    }
+    Ok(())
+}
+
+fn gen(options: GenOptions) -> Result<(), String> {
+    let generated = inputgen::generate(options.day, options.size, options.seed)
+        .ok_or(format!("day {} has no input generator", options.day))?;
+    print!("{}", generated.input);
+    for part in Part::ALL {
+        let answer = generated.answer(part).unwrap_or("-");
+        eprintln!("Day {:>2} part {}: {}", options.day, part.number(), answer);
+    }
+    Ok(())
+}
+
+fn usage_error(message: String) -> ! {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let result = match args.next().as_deref() {
+        Some("run") => run(parse_run_options(args).unwrap_or_else(|e| usage_error(e))),
+        Some("verify") => verify(parse_verify_options(args).unwrap_or_else(|e| usage_error(e))),
+        Some("gen") => gen(parse_gen_options(args).unwrap_or_else(|e| usage_error(e))),
+        Some("passwords") => {
+            passwords(parse_passwords_options(args).unwrap_or_else(|e| usage_error(e)))
+        }
+        Some("passports") => {
+            passports(parse_passports_options(args).unwrap_or_else(|e| usage_error(e)))
+        }
+        Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(command) => usage_error(format!("unknown command: {}", command)),
+        None => usage_error("missing command".to_string()),
+    };
+    if let Err(message) = result {
+        eprintln!("error: {}", message);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> impl Iterator<Item = String> + '_ {
+        line.split_whitespace().map(String::from)
+    }
+
+    #[test]
+    fn run_options() {
+        let options = parse_run_options(args("--day 13 --part 2 --input foo.txt")).unwrap();
+        assert_eq!(options.day, Some(13));
+        assert_eq!(options.part, Some(Part::Two));
+        assert_eq!(options.input, Some(Source::Path(PathBuf::from("foo.txt"))));
+        assert!(!options.all);
+        assert!(parse_run_options(args("--all")).unwrap().all);
+        let options = parse_run_options(args("--day 1 --input - --input-dir elsewhere")).unwrap();
+        assert_eq!(options.input, Some(Source::Stdin));
+        assert_eq!(options.input_dir, Some(PathBuf::from("elsewhere")));
+        assert_eq!(options.format, Format::Table);
+        let options = parse_run_options(args("--all --format csv")).unwrap();
+        assert_eq!(options.format, Format::Csv);
+        assert_eq!(
+            parse_run_options(args("--all --jobs 3")).unwrap().jobs,
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn invalid_run_options() {
+        assert!(parse_run_options(args("")).is_err());
+        assert!(parse_run_options(args("--day")).is_err());
+        assert!(parse_run_options(args("--day 3 --part 3")).is_err());
+        assert!(parse_run_options(args("--all --day 3")).is_err());
+        assert!(parse_run_options(args("--all --input foo.txt")).is_err());
+        assert!(parse_run_options(args("--all --format xml")).is_err());
+        assert!(parse_run_options(args("--all --jobs 0")).is_err());
+    }
+
+    #[test]
+    fn verify_options() {
+        let options = parse_verify_options(args("--manifest a.toml --input-dir b")).unwrap();
+        assert_eq!(options.manifest, Some(PathBuf::from("a.toml")));
+        assert_eq!(options.input_dir, Some(PathBuf::from("b")));
+        assert!(parse_verify_options(args("")).unwrap().manifest.is_none());
+        assert!(parse_verify_options(args("--manifest")).is_err());
+        assert!(parse_verify_options(args("--day 3")).is_err());
+    }
+
+    #[test]
+    fn gen_options() {
+        let options = parse_gen_options(args("--day 9 --size 100000 --seed 42")).unwrap();
+        assert_eq!((options.day, options.size, options.seed), (9, 100000, 42));
+        let options = parse_gen_options(args("--day 1")).unwrap();
+        assert_eq!((options.size, options.seed), (1000, 0));
+        assert!(parse_gen_options(args("--size 10")).is_err());
+        assert!(parse_gen_options(args("--day 1 --size 0")).is_err());
+        assert!(parse_gen_options(args("--day 1 --seed -1")).is_err());
+    }
+
+    #[test]
+    fn passwords_options() {
+        let options = parse_passwords_options(args("--policy positions --format json")).unwrap();
+        assert_eq!(options.policy.as_deref(), Some("positions"));
+        assert_eq!(options.format, Format::Json);
+        let options = parse_passwords_options(args("--input -")).unwrap();
+        assert_eq!(options.input, Some(Source::Stdin));
+        assert_eq!(options.format, Format::Table);
+        assert!(parse_passwords_options(args("--format csv")).is_err());
+        assert!(parse_passwords_options(args("--day 2")).is_err());
+    }
+
+    #[test]
+    fn passports_options() {
+        let options = parse_passports_options(args("--schema ids.json --format json")).unwrap();
+        assert_eq!(options.schema, Some(PathBuf::from("ids.json")));
+        assert_eq!(options.format, Format::Json);
+        assert!(parse_passports_options(args("")).unwrap().schema.is_none());
+        assert!(parse_passports_options(args("--format csv")).is_err());
+        assert!(parse_passports_options(args("--schema")).is_err());
+    }
+}
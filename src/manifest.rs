@@ -0,0 +1,276 @@
+use crate::input::Inputs;
+use crate::{solution, solutions, Part};
+use serde::Deserialize;
+use std::fmt::{self, Display};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::{fs, io};
+
+/// Default manifest, looked up inside the input directory.
+pub const MANIFEST_FILE: &str = "answers.toml";
+
+/// Expected answers per day, part and input file.
+///
+/// ```toml
+/// [[answers]]
+/// day = 13
+/// input = "alice/day13.txt"  # relative to the input directory, day13.txt by default
+/// part1 = 3606
+/// part2 = "379786358533423"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    #[serde(default)]
+    pub answers: Vec<Entry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Entry {
+    pub day: u8,
+    pub input: Option<String>,
+    pub part1: Option<Answer>,
+    pub part2: Option<Answer>,
+}
+
+/// An expected answer, written either as a number or as a string.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Answer {
+    Number(i64),
+    Text(String),
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(n) => n.fmt(f),
+            Answer::Text(text) => text.fmt(f),
+        }
+    }
+}
+
+impl Entry {
+    pub fn input(&self) -> String {
+        self.input
+            .clone()
+            .unwrap_or_else(|| format!("day{}.txt", self.day))
+    }
+
+    pub fn expected(&self, part: Part) -> Option<&Answer> {
+        match part {
+            Part::One => self.part1.as_ref(),
+            Part::Two => self.part2.as_ref(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ManifestError {
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+    Invalid {
+        path: PathBuf,
+        error: toml::de::Error,
+    },
+}
+
+impl Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestError::Io { path, error } => {
+                write!(f, "can't read manifest {}: {}", path.display(), error)
+            }
+            ManifestError::Invalid { path, error } => {
+                write!(f, "invalid manifest {}: {}", path.display(), error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {}
+
+impl FromStr for Manifest {
+    type Err = toml::de::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s)
+    }
+}
+
+impl Manifest {
+    pub fn load(path: &Path) -> Result<Self, ManifestError> {
+        let text = fs::read_to_string(path).map_err(|error| ManifestError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        text.parse().map_err(|error| ManifestError::Invalid {
+            path: path.to_path_buf(),
+            error,
+        })
+    }
+
+    /// Runs every recorded answer, plus a missing check for each part of an
+    /// implemented day with nothing recorded. Sorted by day, then manifest order.
+    pub fn verify(&self, inputs: &Inputs) -> Vec<Check> {
+        let mut checks = Vec::new();
+        for runner in solutions() {
+            let day = runner.day();
+            if !self.answers.iter().any(|entry| entry.day == day) {
+                for &part in &Part::ALL {
+                    checks.push(Check::new(day, part, format!("day{}.txt", day), None));
+                }
+            }
+        }
+        for entry in &self.answers {
+            let loaded = solution(entry.day)
+                .ok_or_else(|| format!("day {} is not implemented", entry.day))
+                .and_then(|runner| {
+                    let input = inputs.file(&entry.input()).map_err(|e| e.to_string())?;
+                    Ok((runner, input))
+                });
+            for &part in &Part::ALL {
+                let expected = entry.expected(part).map(Answer::to_string);
+                let mut check = Check::new(entry.day, part, entry.input(), expected);
+                if let Some(expected) = &check.expected {
+                    check.status = match &loaded {
+                        Ok((runner, input)) => match runner.run(part, input) {
+                            Ok(actual) if actual == *expected => Status::Pass,
+                            Ok(actual) => Status::Fail(actual),
+                            Err(error) => Status::Error(error.to_string()),
+                        },
+                        Err(error) => Status::Error(error.clone()),
+                    };
+                }
+                checks.push(check);
+            }
+        }
+        checks.sort_by_key(|check| check.day);
+        checks
+    }
+}
+
+/// The outcome of checking one part of one input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub day: u8,
+    pub part: Part,
+    pub input: String,
+    pub expected: Option<String>,
+    pub status: Status,
+}
+
+impl Check {
+    fn new(day: u8, part: Part, input: String, expected: Option<String>) -> Self {
+        Check {
+            day,
+            part,
+            input,
+            expected,
+            status: Status::Missing,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    /// Holds the answer the solver gave instead.
+    Fail(String),
+    /// Nothing recorded, so nothing was run.
+    Missing,
+    /// The input or the solver failed before giving an answer.
+    Error(String),
+}
+
+impl Status {
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Status::Fail(_) | Status::Error(_))
+    }
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Status::Pass => "pass",
+            Status::Fail(_) => "FAIL",
+            Status::Missing => "missing",
+            Status::Error(_) => "ERROR",
+        };
+        f.pad(label)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_examples() {
+        let manifest: Manifest = r#"
+            [[answers]]
+            day = 13
+            input = "day13_example1.txt"
+            part1 = 295
+            part2 = "1068781"
+
+            [[answers]]
+            day = 3
+            input = "day3_example.txt"
+            part2 = 1
+
+            [[answers]]
+            day = 5
+            input = "nowhere.txt"
+            part1 = 1
+
+            [[answers]]
+            day = 25
+            part1 = 1
+        "#
+        .parse()
+        .unwrap();
+        let checks = manifest.verify(&Inputs::bundled());
+        let status = |day, part| {
+            let check = checks
+                .iter()
+                .find(|c| c.day == day && c.part == part)
+                .unwrap();
+            check.status.clone()
+        };
+        assert_eq!(status(13, Part::One), Status::Pass);
+        assert_eq!(status(13, Part::Two), Status::Pass);
+        assert_eq!(status(3, Part::One), Status::Missing);
+        assert_eq!(status(3, Part::Two), Status::Fail("336".to_string()));
+        assert!(matches!(status(5, Part::One), Status::Error(_)));
+        assert_eq!(status(5, Part::Two), Status::Missing);
+        assert_eq!(
+            status(25, Part::One),
+            Status::Error("day 25 is not implemented".to_string())
+        );
+        assert_eq!(status(1, Part::One), Status::Missing);
+        assert_eq!(checks.len(), 2 * 19);
+        assert!(checks.windows(2).all(|w| w[0].day <= w[1].day));
+    }
+
+    #[test]
+    fn bundled_manifest_covers_every_day() {
+        let path = Inputs::bundled().dir().join(MANIFEST_FILE);
+        let manifest = Manifest::load(&path).unwrap();
+        for runner in solutions() {
+            let entry = manifest.answers.iter().find(|e| e.day == runner.day());
+            assert!(entry.is_some_and(|e| e.part1.is_some() && e.part2.is_some()));
+        }
+    }
+
+    #[test]
+    fn invalid_manifest() {
+        assert!("[[answers]]\nday = 1\npart3 = 4"
+            .parse::<Manifest>()
+            .is_err());
+        assert!("[[answers]]\npart1 = 4".parse::<Manifest>().is_err());
+    }
+}
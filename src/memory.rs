@@ -0,0 +1,110 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// Heap usage of a measured closure. Everything is zero unless the binary
+/// installs `CountingAllocator` as its global allocator.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Usage {
+    pub allocations: usize,
+    /// Most bytes alive at once, on top of what was alive before.
+    pub peak_bytes: usize,
+    /// Bytes still alive afterwards, like a parsed input.
+    pub retained_bytes: usize,
+}
+
+thread_local! {
+    //Counters are per thread, so days measured in parallel don't mix
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    static LIVE: Cell<usize> = const { Cell::new(0) };
+    static PEAK: Cell<usize> = const { Cell::new(0) };
+}
+
+/// The system allocator, counting what each thread allocates.
+pub struct CountingAllocator;
+
+fn allocated(size: usize) {
+    //try_with: the counters may be gone while the thread exits
+    let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+    let _ = LIVE.try_with(|live| {
+        let now = live.get() + size;
+        live.set(now);
+        let _ = PEAK.try_with(|peak| peak.set(peak.get().max(now)));
+    });
+}
+
+fn freed(size: usize) {
+    //Memory from another thread can make this go below zero
+    let _ = LIVE.try_with(|live| live.set(live.get().saturating_sub(size)));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            allocated(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            allocated(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        freed(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            freed(layout.size());
+            allocated(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Runs `f` and reports what it allocated on this thread.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Usage) {
+    let allocations = ALLOCATIONS.with(Cell::get);
+    let live = LIVE.with(Cell::get);
+    let outer_peak = PEAK.with(|peak| peak.replace(live));
+    let result = f();
+    let peak = PEAK.with(|peak| peak.replace(outer_peak.max(peak.get())));
+    let usage = Usage {
+        allocations: ALLOCATIONS.with(Cell::get) - allocations,
+        peak_bytes: peak - live,
+        retained_bytes: LIVE.with(Cell::get).saturating_sub(live),
+    };
+    (result, usage)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn usage() {
+        let (kept, usage) = measure(|| {
+            let _scratch = vec![0u8; 4096];
+            vec![0u8; 1024]
+        });
+        assert_eq!(usage.allocations, 2);
+        assert_eq!(usage.peak_bytes, 4096 + 1024);
+        assert_eq!(usage.retained_bytes, 1024);
+        drop(kept);
+        let ((), nested) = measure(|| {
+            let (_, inner) = measure(|| vec![0u8; 100]);
+            assert_eq!(inner.peak_bytes, 100);
+        });
+        assert_eq!(nested.peak_bytes, 100);
+    }
+}
@@ -0,0 +1,261 @@
+use crate::error::{AocError, Result};
+use std::str::FromStr;
+
+/// A numbered line of the input, so parsers can point at the offending text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Line<'a> {
+    pub number: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    pub fn new(number: usize, text: &'a str) -> Self {
+        Line { number, text }
+    }
+
+    /// Column of `part`, which must be a slice of this line. Falls back to the
+    /// first column for anything else, like `""` literals.
+    pub fn column(&self, part: &str) -> usize {
+        let start = self.text.as_ptr() as usize;
+        let offset = (part.as_ptr() as usize).wrapping_sub(start);
+        match self.text.get(..offset) {
+            Some(before) if offset + part.len() <= self.text.len() => before.chars().count() + 1,
+            _ => 1,
+        }
+    }
+
+    pub fn error(&self, part: &str, message: impl Into<String>) -> AocError {
+        AocError::parse(self.number, self.column(part), message)
+    }
+
+    /// Error for a line that ended before `what` was found.
+    pub fn missing(&self, what: &str) -> AocError {
+        AocError::parse(
+            self.number,
+            self.text.chars().count() + 1,
+            format!("expected {}", what),
+        )
+    }
+
+    /// Parses `part`, a slice of this line, reporting where it failed.
+    pub fn parse<T: FromStr>(&self, part: &str) -> Result<T> {
+        part.parse()
+            .map_err(|_| self.error(part, format!("invalid number `{}`", part)))
+    }
+
+    /// Moves an error found parsing `part` on its own to where `part` is in this line.
+    pub fn locate(&self, part: &str, error: AocError) -> AocError {
+        match error {
+            AocError::Parse {
+                line: 1,
+                column,
+                message,
+            } => AocError::parse(self.number, self.column(part) + column - 1, message),
+            error => error,
+        }
+    }
+
+    /// Numbers separated by `separator`, like `7,13,x,59`.
+    pub fn list<T: FromStr>(&self, separator: &str) -> Result<Vec<T>> {
+        self.text.split(separator).map(|n| self.parse(n)).collect()
+    }
+
+    /// Whitespace separated `key<separator>value` pairs, like `ecl:gry pid:860033327`.
+    pub fn pairs(&self, separator: char) -> Result<Vec<(&'a str, &'a str)>> {
+        self.text
+            .split_whitespace()
+            .map(|pair| {
+                pair.split_once(separator).ok_or_else(|| {
+                    let message = format!("expected `key{}value`, found `{}`", separator, pair);
+                    self.error(pair, message)
+                })
+            })
+            .collect()
+    }
+
+    /// Splits the line following `pattern`, where each `{name}` stands for a
+    /// field. A field ends where the text after it first shows up, except
+    /// the last one, which takes everything up to the end of the pattern.
+    ///
+    /// ```
+    /// # use aoc2020::parse::Line;
+    /// let line = Line::new(1, "1-3 a: abcde");
+    /// let [min, max, letter, password] = line.fields("{min}-{max} {letter}: {password}").unwrap();
+    /// assert_eq!((min, max, letter, password), ("1", "3", "a", "abcde"));
+    /// ```
+    pub fn fields<const N: usize>(&self, pattern: &str) -> Result<[&'a str; N]> {
+        let literals = literals(pattern);
+        assert_eq!(
+            literals.len(),
+            N + 1,
+            "`{}` doesn't have {} fields",
+            pattern,
+            N
+        );
+        let expected = || {
+            let mut expected = literals[0].to_string();
+            for (name, literal) in names(pattern).zip(&literals[1..]) {
+                expected.push_str(&format!("<{}>{}", name, literal));
+            }
+            format!("expected `{}`", expected)
+        };
+        let mut rest = self
+            .text
+            .strip_prefix(literals[0])
+            .ok_or_else(|| self.error(self.text, expected()))?;
+        let mut fields = [""; N];
+        for (i, literal) in literals[1..].iter().enumerate() {
+            let end = if i + 1 == N {
+                rest.strip_suffix(literal).map(str::len)
+            } else {
+                rest.find(literal)
+            };
+            let end = end.ok_or_else(|| self.error(rest, expected()))?;
+            fields[i] = &rest[..end];
+            rest = &rest[end + literal.len()..];
+        }
+        //Only a pattern without fields can leave something behind
+        if !rest.is_empty() {
+            return Err(self.error(rest, expected()));
+        }
+        Ok(fields)
+    }
+}
+
+//Text around the fields of a pattern, one more than fields there are
+fn literals(pattern: &str) -> Vec<&str> {
+    let mut literals = Vec::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        let end = start + rest[start..].find('}').expect("unclosed `{` in pattern");
+        literals.push(&rest[..start]);
+        rest = &rest[end + 1..];
+    }
+    literals.push(rest);
+    assert!(
+        literals[1..literals.len() - 1]
+            .iter()
+            .all(|l| !l.is_empty()),
+        "fields in `{}` need some text between them",
+        pattern
+    );
+    literals
+}
+
+fn names(pattern: &str) -> impl Iterator<Item = &str> {
+    pattern
+        .split('{')
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}'))
+        .map(|(name, _)| name)
+}
+
+/// Numbered lines of the input.
+pub fn lines(input: &str) -> impl Iterator<Item = Line<'_>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, text)| Line::new(i + 1, text))
+}
+
+/// Groups of lines separated by blank lines.
+pub fn blocks(input: &str) -> impl Iterator<Item = Vec<Line<'_>>> {
+    let mut lines = lines(input).peekable();
+    std::iter::from_fn(move || {
+        while lines.next_if(|line| line.text.is_empty()).is_some() {}
+        let mut block = Vec::new();
+        while let Some(line) = lines.next_if(|line| !line.text.is_empty()) {
+            block.push(line);
+        }
+        Some(block).filter(|block| !block.is_empty())
+    })
+}
+
+/// One number per line.
+pub fn numbers<T: FromStr>(input: &str) -> Result<Vec<T>> {
+    lines(input).map(|line| line.parse(line.text)).collect()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn positions() {
+        let line = Line::new(3, "1-3 a: abcde");
+        assert_eq!(line.column(&line.text[4..5]), 5);
+        assert_eq!(line.column("elsewhere"), 1);
+        assert_eq!(
+            line.parse::<usize>(&line.text[7..])
+                .unwrap_err()
+                .to_string(),
+            "line 3, column 8: invalid number `abcde`"
+        );
+        assert_eq!(
+            line.missing("a password").to_string(),
+            "line 3, column 13: expected a password"
+        );
+        let located = line.locate(&line.text[7..], AocError::parse(1, 2, "bad"));
+        assert_eq!(located.to_string(), "line 3, column 9: bad");
+    }
+
+    #[test]
+    fn fields() {
+        let line = Line::new(2, "mem[8] = 11");
+        assert_eq!(
+            line.fields("mem[{address}] = {value}").unwrap(),
+            ["8", "11"]
+        );
+        assert_eq!(line.fields("{target} = {value}").unwrap(), ["mem[8]", "11"]);
+        let error = line.fields::<2>("mask{x} = {value}").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 1: expected `mask<x> = <value>`"
+        );
+        let line = Line::new(1, "light red bags contain 1 bright white bag.");
+        let [color, contents] = line.fields("{color} bags contain {contents}.").unwrap();
+        assert_eq!((color, contents), ("light red", "1 bright white bag"));
+        let error = Line::new(1, "1-3 a").fields::<4>("{min}-{max} {letter}: {password}");
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "line 1, column 5: expected `<min>-<max> <letter>: <password>`"
+        );
+    }
+
+    #[test]
+    fn lists_and_pairs() {
+        let line = Line::new(1, "7,13,x");
+        assert_eq!(
+            line.list::<u32>(",").unwrap_err().to_string(),
+            "line 1, column 6: invalid number `x`"
+        );
+        let line = Line::new(4, "ecl:gry  pid:860033327");
+        assert_eq!(
+            line.pairs(':').unwrap(),
+            [("ecl", "gry"), ("pid", "860033327")]
+        );
+        let error = Line::new(1, "ecl:gry pid").pairs(':').unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 9: expected `key:value`, found `pid`"
+        );
+    }
+
+    #[test]
+    fn blocks_and_numbers() {
+        let input = "\na\nb\n\n\nc\n";
+        let blocks: Vec<Vec<Line>> = blocks(input).collect();
+        assert_eq!(
+            blocks,
+            [
+                vec![Line::new(2, "a"), Line::new(3, "b")],
+                vec![Line::new(6, "c")]
+            ]
+        );
+        assert_eq!(numbers::<u8>("1\n2\n").unwrap(), [1, 2]);
+        assert_eq!(
+            numbers::<u8>("1\n256").unwrap_err().to_string(),
+            "line 2, column 1: invalid number `256`"
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use crate::memory::{self, Usage};
+use crate::{Driver, Part, Result, Runner};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The answer to one part of a day, with what it took to get it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Record {
+    pub day: u8,
+    pub part: u8,
+    pub answer: String,
+    /// Parsing is shared by both parts, so both records carry the same time.
+    pub parse_ns: u64,
+    pub solve_ns: u64,
+    pub allocations: usize,
+    /// Most heap bytes alive at once while parsing and solving this part.
+    pub peak_bytes: usize,
+}
+
+impl Record {
+    pub fn parse_time(&self) -> Duration {
+        Duration::from_nanos(self.parse_ns)
+    }
+
+    pub fn solve_time(&self) -> Duration {
+        Duration::from_nanos(self.solve_ns)
+    }
+}
+
+struct Measure<'a> {
+    day: u8,
+    parts: &'a [Part],
+    parse_time: Duration,
+    parse_usage: Usage,
+    records: Vec<Record>,
+}
+
+impl Driver for Measure<'_> {
+    fn parse(&mut self, parse: &mut dyn FnMut() -> Result<()>) -> Result<()> {
+        let ((result, elapsed), usage) = memory::measure(|| timed(parse));
+        self.parse_time = elapsed;
+        self.parse_usage = usage;
+        result
+    }
+
+    fn part(&mut self, part: Part, solve: &mut dyn FnMut() -> Result<String>) -> Result<()> {
+        if !self.parts.contains(&part) {
+            return Ok(());
+        }
+        let ((answer, elapsed), usage) = memory::measure(|| timed(solve));
+        let parsed = &self.parse_usage;
+        self.records.push(Record {
+            day: self.day,
+            part: part.number(),
+            answer: answer?,
+            parse_ns: self.parse_time.as_nanos() as u64,
+            solve_ns: elapsed.as_nanos() as u64,
+            allocations: parsed.allocations + usage.allocations,
+            peak_bytes: parsed
+                .peak_bytes
+                .max(parsed.retained_bytes + usage.peak_bytes),
+        });
+        Ok(())
+    }
+}
+
+fn timed<T>(f: &mut dyn FnMut() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+/// Solves the given parts of a day, parsing the input once.
+pub fn measure(runner: &dyn Runner, input: &str, parts: &[Part]) -> Result<Vec<Record>> {
+    let mut measure = Measure {
+        day: runner.day(),
+        parts,
+        parse_time: Duration::default(),
+        parse_usage: Usage::default(),
+        records: Vec::new(),
+    };
+    runner.drive(input, &mut measure)?;
+    Ok(measure.records)
+}
+
+/// Some parts of a day to solve, parsing the input once for all of them.
+#[derive(Clone)]
+pub struct Task<'a> {
+    pub runner: &'a dyn Runner,
+    pub input: &'a str,
+    pub parts: Vec<Part>,
+}
+
+/// Measures every task using up to `jobs` threads. `done` gets the results in
+/// the same order as `tasks`, as soon as each one and those before it finish.
+/// After a task fails no more are started, so `done` may not see them all.
+pub fn measure_all(tasks: &[Task], jobs: usize, mut done: impl FnMut(&Task, Result<Vec<Record>>)) {
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, tasks.len().max(1)) {
+            let sender = sender.clone();
+            let (next, failed) = (&next, &failed);
+            scope.spawn(move || {
+                while !failed.load(Ordering::Relaxed) {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(task) = tasks.get(i) else {
+                        break;
+                    };
+                    let result = measure(task.runner, task.input, &task.parts);
+                    failed.fetch_or(result.is_err(), Ordering::Relaxed);
+                    if sender.send((i, result)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+        //Holds results that finished before some earlier task
+        let mut pending = BTreeMap::new();
+        let mut ready = 0;
+        for (i, result) in receiver {
+            pending.insert(i, result);
+            while let Some(result) = pending.remove(&ready) {
+                done(&tasks[ready], result);
+                ready += 1;
+            }
+        }
+    });
+}
+
+pub fn to_json(records: &[Record]) -> String {
+    serde_json::to_string_pretty(records).expect("records are plain data")
+}
+
+pub fn to_csv(records: &[Record]) -> String {
+    let mut csv = String::from("day,part,answer,parse_ns,solve_ns,allocations,peak_bytes\n");
+    for record in records {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{}\n",
+            record.day,
+            record.part,
+            csv_field(&record.answer),
+            record.parse_ns,
+            record.solve_ns,
+            record.allocations,
+            record.peak_bytes
+        ));
+    }
+    csv
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::day13::Day13;
+
+    fn record(answer: &str) -> Record {
+        Record {
+            day: 21,
+            part: 2,
+            answer: answer.to_string(),
+            parse_ns: 1500,
+            solve_ns: 20,
+            allocations: 3,
+            peak_bytes: 64,
+        }
+    }
+
+    #[test]
+    fn measure_parts() {
+        let input = "939\n7,13,x,x,59,x,31,19";
+        let records = measure(&Day13, input, &[Part::Two]).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!((records[0].day, records[0].part), (13, 2));
+        assert_eq!(records[0].answer, "1068781");
+        assert!(measure(&Day13, "939\n7,a", &Part::ALL).is_err());
+    }
+
+    #[test]
+    fn measure_in_order() {
+        let inputs = ["939\n7,13,x,x,59,x,31,19", "1\n17,x,13,19", "1\n67,7,59,61"];
+        let tasks: Vec<Task> = inputs
+            .iter()
+            .flat_map(|input| {
+                Part::ALL.iter().map(move |&part| Task {
+                    runner: &Day13,
+                    input,
+                    parts: vec![part],
+                })
+            })
+            .collect();
+        let mut answers = vec![];
+        measure_all(&tasks, 4, |_task, records| {
+            answers.extend(records.unwrap().into_iter().map(|r| r.answer))
+        });
+        assert_eq!(answers, ["295", "1068781", "156", "3417", "42", "754018"]);
+        let mut results = vec![];
+        let failing = Task {
+            input: "939\n7,a",
+            ..tasks[0].clone()
+        };
+        measure_all(&[tasks[0].clone(), failing], 1, |_, result| {
+            results.push(result.is_ok())
+        });
+        assert_eq!(results, [true, false]);
+    }
+
+    #[test]
+    fn formats() {
+        let records = [record("mxmxvkd,sqjhc"), record("say \"hi\"")];
+        assert_eq!(
+            to_csv(&records),
+            "day,part,answer,parse_ns,solve_ns,allocations,peak_bytes\n\
+             21,2,\"mxmxvkd,sqjhc\",1500,20,3,64\n\
+             21,2,\"say \"\"hi\"\"\",1500,20,3,64\n"
+        );
+        let json: serde_json::Value = serde_json::from_str(&to_json(&records[..1])).unwrap();
+        assert_eq!(json[0]["answer"], "mxmxvkd,sqjhc");
+        assert_eq!(json[0]["peak_bytes"], 64);
+    }
+}
@@ -0,0 +1,628 @@
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::fmt::{self, Display};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::{fs, io};
+
+/// The fields a `key:value` document, like a day 4 passport, may have and
+/// what their values look like. Loaded from TOML, or JSON with the same shape.
+///
+/// ```toml
+/// allow_unknown = false  # fields not listed make the document invalid
+///
+/// [fields.byr]
+/// range = [1920, 2002]   # an integer between both, included
+/// [fields.hgt]
+/// units = { cm = [150, 193], in = [59, 76] }  # an integer followed by a unit
+/// [fields.hcl]
+/// pattern = "#[0-9a-f]{6}"  # see Pattern
+/// [fields.ecl]
+/// one_of = ["amb", "blu", "brn"]
+/// [fields.cid]
+/// required = false
+/// ```
+///
+/// A field with several validators must pass all of them.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Schema {
+    #[serde(default)]
+    pub allow_unknown: bool,
+    #[serde(default)]
+    pub fields: BTreeMap<String, Field>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Field {
+    #[serde(default = "required")]
+    pub required: bool,
+    pub range: Option<[i64; 2]>,
+    pub units: Option<BTreeMap<String, [i64; 2]>>,
+    pub pattern: Option<Pattern>,
+    pub one_of: Option<Vec<String>>,
+}
+
+fn required() -> bool {
+    true
+}
+
+fn in_range(n: i64, [low, high]: [i64; 2]) -> std::result::Result<(), String> {
+    match (low..=high).contains(&n) {
+        true => Ok(()),
+        false => Err(format!("{} not in {}..={}", n, low, high)),
+    }
+}
+
+/// A value read the way its field's rules need it: a number for a `range`, an
+/// integer with one of the `units`, or the text as it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value<'a> {
+    Number(i64),
+    Measure { amount: i64, unit: &'a str },
+    Text(&'a str),
+}
+
+impl Field {
+    /// The value as a number or a measure, if the rules want one and it is,
+    /// or else as text.
+    pub fn read<'a>(&self, value: &'a str) -> Value<'a> {
+        if self.range.is_some() {
+            return value.parse().map_or(Value::Text(value), Value::Number);
+        }
+        let measure = self.units.iter().flatten().find_map(|(unit, _range)| {
+            let amount = value.strip_suffix(unit.as_str())?.parse().ok()?;
+            let unit = &value[value.len() - unit.len()..];
+            Some(Value::Measure { amount, unit })
+        });
+        measure.unwrap_or(Value::Text(value))
+    }
+
+    pub fn accepts(&self, value: &str) -> bool {
+        self.check(value, self.read(value)).is_ok()
+    }
+
+    /// Whether the value, as written and as read, follows every rule.
+    pub fn accepts_read(&self, value: &str, read: Value) -> bool {
+        self.check(value, read).is_ok()
+    }
+
+    /// The first rule the value breaks, and why.
+    fn check(&self, value: &str, read: Value) -> Result<(), (&'static str, String)> {
+        if let Some(range) = self.range {
+            let checked = match read {
+                Value::Number(n) => in_range(n, range),
+                _ => Err(format!("`{}` is not a number", value)),
+            };
+            checked.map_err(|reason| ("range", reason))?;
+        }
+        if let Some(units) = &self.units {
+            let checked = match read {
+                Value::Measure { amount, unit } => in_range(amount, units[unit]),
+                //Ending with a unit, but not after a number
+                _ => match units
+                    .keys()
+                    .find_map(|unit| value.strip_suffix(unit.as_str()))
+                {
+                    Some(amount) => Err(format!("`{}` is not a number", amount)),
+                    None => {
+                        let units: Vec<&str> = units.keys().map(String::as_str).collect();
+                        Err(format!("expected a unit, {}", units.join(" or ")))
+                    }
+                },
+            };
+            checked.map_err(|reason| ("units", reason))?;
+        }
+        if let Some(pattern) = &self.pattern {
+            if !pattern.matches(value) {
+                return Err(("pattern", format!("doesn't match `{}`", pattern)));
+            }
+        }
+        if let Some(values) = &self.one_of {
+            if !values.iter().any(|v| v == value) {
+                return Err(("one_of", format!("not one of {}", values.join(", "))));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Everything wrong with a document.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Diagnosis {
+    pub missing: Vec<String>,
+    pub invalid: Vec<Invalid>,
+    pub unknown: Vec<String>,
+    /// Fields given more than once, filled in by whoever parsed the document.
+    pub repeated: Vec<String>,
+}
+
+/// A field with a value that breaks a rule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Invalid {
+    pub field: String,
+    pub value: String,
+    pub rule: &'static str,
+    pub reason: String,
+}
+
+impl Diagnosis {
+    pub fn is_valid(&self) -> bool {
+        self.missing.is_empty()
+            && self.invalid.is_empty()
+            && self.unknown.is_empty()
+            && self.repeated.is_empty()
+    }
+
+    /// Each problem in words, like `hgt=190in: 190 not in 59..=76`.
+    pub fn problems(&self) -> Vec<String> {
+        let missing = self.missing.iter().map(|key| format!("missing {}", key));
+        let invalid = self
+            .invalid
+            .iter()
+            .map(|i| format!("{}={}: {}", i.field, i.value, i.reason));
+        let unknown = self.unknown.iter().map(|key| format!("unknown {}", key));
+        let repeated = self.repeated.iter().map(|key| format!("repeated {}", key));
+        missing
+            .chain(invalid)
+            .chain(unknown)
+            .chain(repeated)
+            .collect()
+    }
+
+    /// Each problem without the values, to tell how often they happen.
+    pub fn kinds(&self) -> Vec<String> {
+        let missing = self.missing.iter().map(|key| format!("{}: missing", key));
+        let invalid = self
+            .invalid
+            .iter()
+            .map(|i| format!("{}: {}", i.field, i.rule));
+        let unknown = self.unknown.iter().map(|key| format!("{}: unknown", key));
+        let repeated = self.repeated.iter().map(|key| format!("{}: repeated", key));
+        missing
+            .chain(invalid)
+            .chain(unknown)
+            .chain(repeated)
+            .collect()
+    }
+}
+
+impl Schema {
+    pub fn load(path: &Path) -> Result<Self, SchemaError> {
+        let text = fs::read_to_string(path).map_err(|error| SchemaError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        let schema = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Schema::from_json(&text),
+            _ => text
+                .parse()
+                .map_err(|e: toml::de::Error| e.message().to_string()),
+        };
+        schema.map_err(|message| SchemaError::Invalid {
+            path: path.to_path_buf(),
+            message,
+        })
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        serde_json::from_str(text).map_err(|e| e.to_string())
+    }
+
+    pub fn field(&self, key: &str) -> Option<&Field> {
+        self.fields.get(key)
+    }
+
+    /// Whether documents may have a field with this key.
+    pub fn allows(&self, key: &str) -> bool {
+        self.allow_unknown || self.fields.contains_key(key)
+    }
+
+    pub fn required(&self) -> impl Iterator<Item = &str> {
+        self.fields
+            .iter()
+            .filter(|(_key, field)| field.required)
+            .map(|(key, _field)| key.as_str())
+    }
+
+    /// Whether the document has every required field, whatever their values.
+    pub fn is_complete(&self, document: &HashMap<&str, &str>) -> bool {
+        self.required().all(|key| document.contains_key(key))
+    }
+
+    /// Missing fields in schema order, the rest by key.
+    pub fn diagnose(&self, document: &HashMap<&str, &str>) -> Diagnosis {
+        let missing = self.required().filter(|key| !document.contains_key(key));
+        let mut diagnosis = Diagnosis {
+            missing: missing.map(String::from).collect(),
+            ..Diagnosis::default()
+        };
+        for (&key, &value) in document.iter().sorted() {
+            match self
+                .field(key)
+                .map(|field| field.check(value, field.read(value)))
+            {
+                Some(Ok(())) => {}
+                Some(Err((rule, reason))) => diagnosis.invalid.push(Invalid {
+                    field: key.to_string(),
+                    value: value.to_string(),
+                    rule,
+                    reason,
+                }),
+                None if self.allow_unknown => {}
+                None => diagnosis.unknown.push(key.to_string()),
+            }
+        }
+        diagnosis
+    }
+
+    pub fn is_valid(&self, document: &HashMap<&str, &str>) -> bool {
+        self.is_complete(document)
+            && document.iter().all(|(key, value)| match self.field(key) {
+                Some(field) => field.accepts(value),
+                None => self.allow_unknown,
+            })
+    }
+}
+
+impl FromStr for Schema {
+    type Err = toml::de::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s)
+    }
+}
+
+#[derive(Debug)]
+pub enum SchemaError {
+    Io { path: PathBuf, error: io::Error },
+    Invalid { path: PathBuf, message: String },
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::Io { path, error } => {
+                write!(f, "can't read schema {}: {}", path.display(), error)
+            }
+            SchemaError::Invalid { path, message } => {
+                write!(f, "invalid schema {}: {}", path.display(), message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// A small part of regular expressions, matching whole values: characters,
+/// `.` for any of them, classes like `[0-9a-f]` or `[^#]`, `\d` for a digit
+/// and `\` before any other character to take it as it is. Each of them may be
+/// followed by `?`, `*`, `+`, `{n}` or `{n,m}`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Pattern {
+    source: String,
+    //What each position matches, and how many times
+    items: Vec<(Atom, usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Atom {
+    Any,
+    Char(char),
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+}
+
+impl Atom {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Atom::Any => true,
+            Atom::Char(expected) => c == *expected,
+            Atom::Class { ranges, negated } => {
+                ranges.iter().any(|(low, high)| (low..=high).contains(&&c)) != *negated
+            }
+        }
+    }
+}
+
+impl Pattern {
+    pub fn matches(&self, value: &str) -> bool {
+        let chars: Vec<char> = value.chars().collect();
+        matches(&self.items, &chars)
+    }
+}
+
+fn matches(items: &[(Atom, usize, usize)], chars: &[char]) -> bool {
+    //Every offset the items so far can end at, item by item, so the time only
+    //grows with items times characters however the repetitions overlap
+    let mut ends = vec![false; chars.len() + 1];
+    ends[0] = true;
+    for (atom, min, max) in items {
+        //How many characters in a row from each offset the atom matches
+        let mut run = vec![0; chars.len() + 1];
+        for i in (0..chars.len()).rev() {
+            if atom.matches(chars[i]) {
+                run[i] = run[i + 1] + 1;
+            }
+        }
+        //Each offset reaches a span of the next ones, added up from where they start
+        let mut starts = vec![0i64; chars.len() + 2];
+        for (i, _end) in ends.iter().enumerate().filter(|(_i, &end)| end) {
+            let most = run[i].min(*max);
+            if most >= *min {
+                starts[i + min] += 1;
+                starts[i + most + 1] -= 1;
+            }
+        }
+        let mut reached = 0;
+        for (end, start) in ends.iter_mut().zip(&starts) {
+            reached += start;
+            *end = reached > 0;
+        }
+    }
+    ends[chars.len()]
+}
+
+impl FromStr for Pattern {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Self, String> {
+        let mut chars = source.chars().peekable();
+        let mut items = Vec::new();
+        while let Some(c) = chars.next() {
+            let atom = match c {
+                '.' => Atom::Any,
+                '\\' => match chars.next() {
+                    Some('d') => Atom::Class {
+                        ranges: vec![('0', '9')],
+                        negated: false,
+                    },
+                    Some(c) => Atom::Char(c),
+                    None => return Err("nothing to escape after `\\`".to_string()),
+                },
+                '[' => {
+                    let negated = chars.next_if_eq(&'^').is_some();
+                    let mut ranges = Vec::new();
+                    loop {
+                        let low = match chars.next() {
+                            Some(']') if !ranges.is_empty() => break,
+                            Some(c) => c,
+                            None => return Err("unclosed `[`".to_string()),
+                        };
+                        let high = match chars.peek() {
+                            Some('-') => {
+                                chars.next();
+                                match chars.next() {
+                                    Some(']') | None => return Err("unclosed range".to_string()),
+                                    Some(high) => high,
+                                }
+                            }
+                            _ => low,
+                        };
+                        if low > high {
+                            return Err(format!("reversed range `{}-{}`", low, high));
+                        }
+                        ranges.push((low, high));
+                    }
+                    Atom::Class { ranges, negated }
+                }
+                '?' | '*' | '+' | '{' => return Err(format!("nothing to repeat before `{}`", c)),
+                c => Atom::Char(c),
+            };
+            let (min, max) = match chars.peek() {
+                Some('?') => (0, 1),
+                Some('*') => (0, usize::MAX),
+                Some('+') => (1, usize::MAX),
+                Some('{') => {
+                    chars.next();
+                    let mut repeat = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => repeat.push(c),
+                            None => return Err("unclosed `{`".to_string()),
+                        }
+                    }
+                    let number = |n: &str| {
+                        n.trim()
+                            .parse()
+                            .map_err(|_| format!("invalid repetition `{{{}}}`", repeat))
+                    };
+                    let (min, max) = match repeat.split_once(',') {
+                        Some((min, max)) => (number(min)?, number(max)?),
+                        None => (number(&repeat)?, number(&repeat)?),
+                    };
+                    if min > max {
+                        return Err(format!("invalid repetition `{{{}}}`", repeat));
+                    }
+                    items.push((atom, min, max));
+                    continue;
+                }
+                _ => (1, 1),
+            };
+            if (min, max) != (1, 1) {
+                chars.next();
+            }
+            items.push((atom, min, max));
+        }
+        Ok(Pattern {
+            source: source.to_string(),
+            items,
+        })
+    }
+}
+
+impl TryFrom<String> for Pattern {
+    type Error = String;
+
+    fn try_from(source: String) -> Result<Self, String> {
+        source.parse()
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.source.fmt(f)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns() {
+        let pattern: Pattern = "#[0-9a-f]{6}".parse().unwrap();
+        assert!(pattern.matches("#123abc"));
+        assert!(!pattern.matches("#123abz"));
+        assert!(!pattern.matches("#123abcd"));
+        assert!(!pattern.matches("123abc"));
+        let pattern: Pattern = r"\d{2,3}a?b*.\+[^x]+".parse().unwrap();
+        assert!(pattern.matches("12bbb!+yy"));
+        assert!(pattern.matches("123a?+y"));
+        assert!(!pattern.matches("1a!+y"));
+        assert!(!pattern.matches("123a!+x"));
+        assert!("a{3,1}".parse::<Pattern>().is_err());
+        assert!("+a".parse::<Pattern>().is_err());
+        assert!("[a-".parse::<Pattern>().is_err());
+        assert!("[ab".parse::<Pattern>().is_err());
+        assert!("a{x}".parse::<Pattern>().is_err());
+        assert_eq!("a{3".parse::<Pattern>().unwrap_err(), "unclosed `{`");
+        assert_eq!("a{2,".parse::<Pattern>().unwrap_err(), "unclosed `{`");
+        assert_eq!(
+            "[z-a]".parse::<Pattern>().unwrap_err(),
+            "reversed range `z-a`"
+        );
+        assert!("[a-a]".parse::<Pattern>().is_ok());
+
+        //Backtracking over every way to split the run took seconds here
+        let pattern: Pattern = ("a*".repeat(30) + "b").parse().unwrap();
+        let start = std::time::Instant::now();
+        assert!(!pattern.matches(&"a".repeat(5000)));
+        assert!(pattern.matches(&("a".repeat(5000) + "b")));
+        assert!(start.elapsed() < std::time::Duration::from_secs(1));
+        let pattern: Pattern = "a{2,3}.{0,2}b?".parse().unwrap();
+        for (value, matched) in [
+            ("aa", true),
+            ("aaaab", true),
+            ("aaaaaa", false),
+            ("a", false),
+        ] {
+            assert_eq!(pattern.matches(value), matched, "{}", value);
+        }
+    }
+
+    #[test]
+    fn documents() {
+        let schema: Schema = r#"
+            [fields.age]
+            range = [18, 99]
+            [fields.height]
+            units = { cm = [100, 250], in = [40, 100] }
+            [fields.code]
+            pattern = "[A-Z]{2}-\\d+"
+            one_of = ["AB-1", "CD-22"]
+            [fields.note]
+            required = false
+        "#
+        .parse()
+        .unwrap();
+        let document: HashMap<&str, &str> =
+            [("age", "30"), ("height", "180cm"), ("code", "CD-22")].into();
+        assert!(schema.is_valid(&document));
+        assert_eq!(
+            schema.required().collect::<Vec<_>>(),
+            ["age", "code", "height"]
+        );
+        let invalid = |key, value| {
+            let mut document = document.clone();
+            document.insert(key, value);
+            !schema.is_valid(&document)
+        };
+        assert!(invalid("age", "17"));
+        assert!(invalid("height", "180"));
+        assert!(invalid("height", "30in"));
+        assert!(invalid("code", "EF-3"));
+        assert!(invalid("colour", "red"));
+        assert!(!invalid("note", "anything"));
+        let mut incomplete = document.clone();
+        incomplete.remove("age");
+        assert!(!schema.is_complete(&incomplete));
+
+        let json = r#"{"allow_unknown": true, "fields": {"age": {"range": [18, 99]}}}"#;
+        let schema = Schema::from_json(json).unwrap();
+        assert!(schema.is_valid(&[("age", "30"), ("colour", "red")].into()));
+        assert!(Schema::from_json(r#"{"fields": {"age": {"pattern": "{"}}}"#).is_err());
+        assert!("[fields.age]\nrange = 3".parse::<Schema>().is_err());
+        assert!("[fields.age]\nmaximum = 3".parse::<Schema>().is_err());
+    }
+
+    #[test]
+    fn diagnoses() {
+        let schema: Schema = include_str!("../schemas/passport.toml").parse().unwrap();
+        let document: HashMap<&str, &str> = [
+            ("hgt", "190in"),
+            ("byr", "1900"),
+            ("iyr", "20x"),
+            ("eyr", "2025"),
+            ("hcl", "#12345z"),
+            ("ecl", "red"),
+            ("foo", "bar"),
+        ]
+        .into();
+        let diagnosis = schema.diagnose(&document);
+        assert!(!diagnosis.is_valid());
+        assert_eq!(
+            diagnosis.problems(),
+            vec![
+                "missing pid",
+                "byr=1900: 1900 not in 1920..=2002",
+                "ecl=red: not one of amb, blu, brn, gry, grn, hzl, oth",
+                "hcl=#12345z: doesn't match `#[0-9a-fA-F]{6}`",
+                "hgt=190in: 190 not in 59..=76",
+                "iyr=20x: `20x` is not a number",
+                "unknown foo",
+            ]
+        );
+        assert_eq!(diagnosis.kinds()[..2], ["pid: missing", "byr: range"]);
+        let hgt = |value| {
+            let document = [("hgt", value)].into();
+            schema.diagnose(&document).invalid.pop().map(|i| i.reason)
+        };
+        assert_eq!(hgt("190"), Some("expected a unit, cm or in".to_string()));
+        assert_eq!(hgt("cm"), Some("`` is not a number".to_string()));
+        assert_eq!(hgt("160cm"), None);
+
+        let field = |key| schema.field(key).unwrap();
+        assert_eq!(field("byr").read("1990"), Value::Number(1990));
+        assert_eq!(field("byr").read("19x"), Value::Text("19x"));
+        let measure = Value::Measure {
+            amount: 70,
+            unit: "in",
+        };
+        assert_eq!(field("hgt").read("70in"), measure);
+        assert_eq!(field("hgt").read("70"), Value::Text("70"));
+        assert_eq!(field("hcl").read("#123abc"), Value::Text("#123abc"));
+    }
+
+    #[test]
+    fn load() {
+        let dir = std::env::temp_dir().join(format!("aoc2020-schema-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let toml = dir.join("schema.toml");
+        fs::write(&toml, "[fields.age]\nrange = [18, 99]\n").unwrap();
+        assert!(Schema::load(&toml).unwrap().field("age").is_some());
+        let json = dir.join("schema.json");
+        fs::write(&json, r#"{"fields": {"age": {"one_of": "18"}}}"#).unwrap();
+        let error = Schema::load(&json).unwrap_err().to_string();
+        assert!(error.starts_with("invalid schema"), "{}", error);
+        assert!(Schema::load(&dir.join("missing.toml")).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
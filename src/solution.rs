@@ -0,0 +1,156 @@
+use crate::error::Result;
+use crate::input::normalize;
+use std::fmt::{self, Display};
+
+/// One of the two halves of a daily puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+    pub fn number(self) -> u8 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+
+    pub fn from_number(n: u8) -> Option<Self> {
+        match n {
+            1 => Some(Part::One),
+            2 => Some(Part::Two),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+/// A solved day: how to turn the raw input into something both parts can share,
+/// and how to get each answer from it.
+pub trait Solution {
+    const DAY: u8;
+
+    /// The parsed puzzle input. It may borrow from the raw text.
+    type Parsed<'a>;
+    type Answer1: Display;
+    type Answer2: Display;
+
+    /// Gets the input already normalized when called through a `Runner`,
+    /// see `input::normalize`.
+    fn parse(input: &str) -> Result<Self::Parsed<'_>>;
+    fn part1(parsed: &Self::Parsed<'_>) -> Result<Self::Answer1>;
+    fn part2(parsed: &Self::Parsed<'_>) -> Result<Self::Answer2>;
+}
+
+/// Object safe view of a `Solution`, used by the registry so every day can be
+/// driven the same way regardless of its parsed and answer types.
+pub trait Runner: Sync {
+    fn day(&self) -> u8;
+    /// Normalizes `input`, parses it and solves `part`.
+    fn run(&self, part: Part, input: &str) -> Result<String>;
+    /// Hands each stage of solving `input` to `driver`, parse first. Like
+    /// `run`, it normalizes the input before parsing it.
+    fn drive(&self, input: &str, driver: &mut dyn Driver) -> Result<()>;
+
+    /// Only parses `input`, to tell if it's valid without solving anything.
+    fn check(&self, input: &str) -> Result<()> {
+        self.drive(input, &mut ParseOnly)
+    }
+}
+
+/// Decides how the stages of a day are run, e.g. to time or repeat them.
+/// Every stage may be called any number of times, or not at all.
+pub trait Driver {
+    fn parse(&mut self, parse: &mut dyn FnMut() -> Result<()>) -> Result<()>;
+    fn part(&mut self, part: Part, solve: &mut dyn FnMut() -> Result<String>) -> Result<()>;
+}
+
+struct ParseOnly;
+
+impl Driver for ParseOnly {
+    fn parse(&mut self, parse: &mut dyn FnMut() -> Result<()>) -> Result<()> {
+        parse()
+    }
+
+    fn part(&mut self, _part: Part, _solve: &mut dyn FnMut() -> Result<String>) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<S: Solution + Sync> Runner for S {
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn run(&self, part: Part, input: &str) -> Result<String> {
+        let input = normalize(input);
+        let parsed = S::parse(&input)?;
+        match part {
+            Part::One => S::part1(&parsed).map(|answer| answer.to_string()),
+            Part::Two => S::part2(&parsed).map(|answer| answer.to_string()),
+        }
+    }
+
+    fn drive(&self, input: &str, driver: &mut dyn Driver) -> Result<()> {
+        let input = normalize(input);
+        let mut parsed = None;
+        driver.parse(&mut || {
+            parsed = Some(S::parse(&input)?);
+            Ok(())
+        })?;
+        //The driver skipped parsing, but the parts still need it
+        let parsed = match parsed {
+            Some(parsed) => parsed,
+            None => S::parse(&input)?,
+        };
+        driver.part(Part::One, &mut || {
+            S::part1(&parsed).map(|answer| answer.to_string())
+        })?;
+        driver.part(Part::Two, &mut || {
+            S::part2(&parsed).map(|answer| answer.to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::day13::Day13;
+
+    //Skips parsing and keeps the answers
+    struct Answers(Vec<String>);
+
+    impl Driver for Answers {
+        fn parse(&mut self, _parse: &mut dyn FnMut() -> Result<()>) -> Result<()> {
+            Ok(())
+        }
+
+        fn part(&mut self, _part: Part, solve: &mut dyn FnMut() -> Result<String>) -> Result<()> {
+            self.0.push(solve()?);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn drive_stages() {
+        let input = "939\n7,13,x,x,59,x,31,19";
+        let mut answers = Answers(vec![]);
+        Day13.drive(input, &mut answers).unwrap();
+        assert_eq!(
+            answers.0,
+            vec![Day13.run(Part::One, input).unwrap(), "1068781".to_string()]
+        );
+        assert!(Day13.drive("939\n7,a", &mut answers).is_err());
+        assert!(Day13.check(input).is_ok());
+        assert!(Day13.check("939\n7,a").is_err());
+    }
+}